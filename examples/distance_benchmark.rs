@@ -0,0 +1,73 @@
+//! Times `DistanceSearch` against the `BinaryHeap` search `DistanceBoard::from_owner` used to run
+//!
+//! Run with `cargo run --release --example distance_benchmark`.
+//! The boards come from games of `simple_economy` against itself, so they have grass and recyclers.
+//! That both give the same distances is checked by the tests of `distance_board`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use codingame_keep_off_the_grass::agent;
+use codingame_keep_off_the_grass::board::distance_board::{heap_distances, DistanceBoard, DistanceSearch};
+use codingame_keep_off_the_grass::board::map_generator::MapGenerator;
+use codingame_keep_off_the_grass::board::{Board, Owner};
+
+const SEEDS: u64 = 20;
+const TURNS: u32 = 60;
+const ROUNDS: u32 = 50;
+
+fn collect_boards() -> Vec<Board> {
+    let mut boards = Vec::new();
+    for seed in 1..=SEEDS {
+        let mut board = MapGenerator::new(seed).generate();
+        let mut players = [agent::from_name("simple_economy").unwrap(), agent::from_name("simple_economy").unwrap()];
+        for _ in 0..TURNS {
+            let my_actions = players[0].generate_actions(board.clone());
+            let opponent_actions = players[1].generate_actions(board.swap_perspective());
+            board = board.apply_turn(&my_actions, &opponent_actions);
+            boards.push(board.clone());
+        }
+    }
+    boards
+}
+
+fn time(name: &str, searches: usize, mut run: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        run();
+    }
+    let elapsed = start.elapsed();
+    println!("{:<24} {:>8.2} us per search", name, elapsed.as_secs_f64() * 1e6 / (searches as f64 * ROUNDS as f64));
+    elapsed
+}
+
+fn main() {
+    let boards = collect_boards();
+    let owners = [Owner::Me, Owner::Opponent, Owner::Neutral];
+
+    let searches = boards.len() * owners.len();
+
+    let heap = time("binary heap", searches, || {
+        for board in boards.iter() {
+            for owner in owners {
+                black_box(heap_distances(black_box(board), owner));
+            }
+        }
+    });
+    time("from_owner", searches, || {
+        for board in boards.iter() {
+            for owner in owners {
+                black_box(DistanceBoard::from_owner(black_box(board), owner));
+            }
+        }
+    });
+    let mut search = DistanceSearch::default();
+    let reused = time("reused DistanceSearch", searches, || {
+        for board in boards.iter() {
+            for owner in owners {
+                let distances = black_box(search.from_owner(black_box(board), owner));
+                search.recycle(distances);
+            }
+        }
+    });
+    println!("speed-up {:.1}x", heap.as_secs_f64() / reused.as_secs_f64());
+}
@@ -0,0 +1,201 @@
+//! Lists the action types
+
+pub mod validator;
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const TAUNTS: [&str; 4] = [
+    "Be prepared to get scrapped!",
+    "Let's see if Santa made your Robots run on coal!",
+    "Ah! A good game to you... Unless your name is Jaap!",
+    "Better to give in. You wouldn't want to end up on my naughty list, would you?",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Move {
+        amount: u32,
+        from: (u32, u32),
+        to: (u32, u32),
+    },
+    Build(u32, u32),
+    Spawn(u32, u32, u32),
+    Message(String),
+    Wait,
+}
+
+impl Action {
+    pub fn generate_taunt() -> Self {
+        // Uses the current nanos from 1970 as rng
+        Self::Message(TAUNTS[SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos() as usize % TAUNTS.len()].to_string())
+    }
+
+    pub fn log_turn(actions: impl IntoIterator<Item=Action>) {
+        let mut total_actions: u32 = 0;
+        let mut result = String::new();
+        for action in actions {
+            result.push_str(&format!("{};", action));
+            total_actions += 1;
+        }
+
+        if total_actions == 0 {
+            result.push_str("WAIT;");
+        }
+
+        println!("{}", result);
+    }
+
+    /// Parses a line of `;`-separated commands, as written by `log_turn`
+    ///
+    /// Empty commands, like the one after a trailing `;`, are skipped.
+    /// Like on the server, every `;` ends a command, even inside a message.
+    pub fn parse_turn(line: &str) -> Result<Vec<Action>, ActionParseError> {
+        line.split(';')
+            .filter(|x| !x.trim().is_empty())
+            .map(Action::from_str)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ActionParseError {
+    Empty,
+    UnknownCommand(String),
+    WrongArgumentCount {
+        command: String,
+        expected: usize,
+        found: usize,
+    },
+    InvalidNumber(String),
+}
+
+impl Display for ActionParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty command"),
+            Self::UnknownCommand(command) => write!(f, "unknown command {}", command),
+            Self::WrongArgumentCount { command, expected, found } => {
+                write!(f, "{} expects {} arguments, found {}", command, expected, found)
+            },
+            Self::InvalidNumber(token) => write!(f, "invalid number {}", token),
+        }
+    }
+}
+
+impl Error for ActionParseError {}
+
+impl FromStr for Action {
+    type Err = ActionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (command, rest) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+
+        // messages may contain spaces, so they take the rest of the command as is
+        if command == "MESSAGE" {
+            return Ok(Self::Message(rest.trim().to_string()));
+        }
+
+        let expected = match command {
+            "" => return Err(ActionParseError::Empty),
+            "MOVE" => 5,
+            "BUILD" => 2,
+            "SPAWN" => 3,
+            "WAIT" => 0,
+            _ => return Err(ActionParseError::UnknownCommand(command.to_string())),
+        };
+
+        let arguments = rest
+            .split_whitespace()
+            .map(|x| x.parse::<u32>().map_err(|_| ActionParseError::InvalidNumber(x.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+        match (command, arguments.as_slice()) {
+            ("MOVE", &[amount, from_x, from_y, to_x, to_y]) => Ok(Self::Move {
+                amount,
+                from: (from_x, from_y),
+                to: (to_x, to_y),
+            }),
+            ("BUILD", &[x, y]) => Ok(Self::Build(x, y)),
+            ("SPAWN", &[amount, x, y]) => Ok(Self::Spawn(amount, x, y)),
+            ("WAIT", &[]) => Ok(Self::Wait),
+            _ => Err(ActionParseError::WrongArgumentCount {
+                command: command.to_string(),
+                expected,
+                found: arguments.len(),
+            }),
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Move {amount, from, to} => {
+                write!(f, "MOVE {} {} {} {} {}", amount, from.0, from.1, to.0, to.1)
+            },
+            Self::Build(x, y) => {
+                write!(f, "BUILD {} {}", x, y)
+            },
+            Self::Spawn(amount, x, y) => {
+                write!(f, "SPAWN {} {} {}", amount, x, y)
+            },
+            Self::Message(msg) => {
+                write!(f, "MESSAGE {}", msg)
+            },
+            Self::Wait => {
+                write!(f, "WAIT")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_parses_back() {
+        let actions = vec![
+            Action::Move { amount: 3, from: (1, 2), to: (10, 0) },
+            Action::Build(4, 5),
+            Action::Spawn(2, 0, 7),
+            Action::Message("no grass, no glory".to_string()),
+            Action::Wait,
+        ];
+        let line = actions.iter().map(|x| format!("{};", x)).collect::<String>();
+
+        assert_eq!(Action::parse_turn(&line), Ok(actions));
+    }
+
+    #[test]
+    fn whitespace_and_empty_commands_are_skipped() {
+        assert_eq!(Action::parse_turn("  MOVE  1 2 3\t4 5 ;; WAIT;  "), Ok(vec![
+            Action::Move { amount: 1, from: (2, 3), to: (4, 5) },
+            Action::Wait,
+        ]));
+        assert_eq!(Action::parse_turn(""), Ok(vec![]));
+        assert_eq!(Action::parse_turn("MESSAGE hi;WAIT"), Ok(vec![Action::Message("hi".to_string()), Action::Wait]));
+        // the server splits messages too
+        assert_eq!(Action::parse_turn("MESSAGE a;b"), Err(ActionParseError::UnknownCommand("b".to_string())));
+    }
+
+    #[test]
+    fn errors_name_the_bad_part() {
+        assert_eq!("  ".parse::<Action>(), Err(ActionParseError::Empty));
+        assert_eq!(Action::parse_turn("WAIT;JUMP 1 2"), Err(ActionParseError::UnknownCommand("JUMP".to_string())));
+        assert_eq!("MOVE 1 2 3".parse::<Action>(), Err(ActionParseError::WrongArgumentCount {
+            command: "MOVE".to_string(),
+            expected: 5,
+            found: 3,
+        }));
+        assert_eq!("WAIT 1".parse::<Action>(), Err(ActionParseError::WrongArgumentCount {
+            command: "WAIT".to_string(),
+            expected: 0,
+            found: 1,
+        }));
+        assert_eq!("SPAWN -1 0 0".parse::<Action>(), Err(ActionParseError::InvalidNumber("-1".to_string())));
+    }
+}
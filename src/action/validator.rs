@@ -0,0 +1,241 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use super::Action;
+use super::super::board::simulation::{RECYCLER_COST, ROBOT_COST};
+use super::super::board::{Board, Owner};
+
+/// Why the server would ignore an action
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ActionError {
+    OutOfBounds,
+    /// Moving to the field robots are already on, or to a field no path leads to
+    Unreachable,
+    NotOwned,
+    ZeroAmount,
+    /// All robots on the field have been moved already
+    NoUnitsLeft,
+    NotEnoughMatter {
+        needed: u32,
+        available: u32,
+    },
+    RecyclerPresent,
+    CannotBuild,
+    CannotSpawn,
+}
+
+impl Display for ActionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfBounds => write!(f, "field is out of bounds"),
+            Self::Unreachable => write!(f, "target is unreachable"),
+            Self::NotOwned => write!(f, "field is not owned"),
+            Self::ZeroAmount => write!(f, "amount is zero"),
+            Self::NoUnitsLeft => write!(f, "no units left to move"),
+            Self::NotEnoughMatter { needed, available } => write!(f, "needs {} matter, {} available", needed, available),
+            Self::RecyclerPresent => write!(f, "field has a recycler"),
+            Self::CannotBuild => write!(f, "can't build on field"),
+            Self::CannotSpawn => write!(f, "can't spawn on field"),
+        }
+    }
+}
+
+impl Error for ActionError {}
+
+/// Checks the actions of a turn against what the server would accept
+///
+/// Valid actions use up matter and units, so later actions see what is left. A move of more units
+/// than are left moves all of them, like on the server.
+/// The server executes all builds before the spawns and moves. `validate` checks one action
+/// at a time, in the order it is called; `validate_turn` puts the builds first like the server.
+pub struct TurnValidator {
+    board: Board,
+    matter: u32,
+    movable_units: Vec<u32>,
+}
+
+impl TurnValidator {
+
+    pub fn new(board: &Board) -> Self {
+        Self {
+            board: board.clone(),
+            matter: board.my_matter,
+            movable_units: board.fields
+                .iter()
+                .map(|x| if x.owner == Owner::Me { x.num_units } else { 0 })
+                .collect(),
+        }
+    }
+
+    pub fn validate(&mut self, action: &Action) -> Result<(), ActionError> {
+        match *action {
+            Action::Move { amount, from, to } => self.validate_move(amount, from, to),
+            Action::Build(x, y) => self.validate_build(x, y),
+            Action::Spawn(amount, x, y) => self.validate_spawn(amount, x, y),
+            Action::Message(_) | Action::Wait => Ok(()),
+        }
+    }
+
+    /// The result for every action of a turn, in the order of `actions`
+    pub fn validate_turn(&mut self, actions: &[Action]) -> Vec<Result<(), ActionError>> {
+        let mut results = vec![Ok(()); actions.len()];
+        let (builds, others): (Vec<_>, Vec<_>) = actions
+            .iter()
+            .enumerate()
+            .partition(|(_, action)| matches!(action, Action::Build(..)));
+
+        for (i, action) in builds.into_iter().chain(others) {
+            results[i] = self.validate(action);
+        }
+        results
+    }
+
+    fn validate_move(&mut self, amount: u32, from: (u32, u32), to: (u32, u32)) -> Result<(), ActionError> {
+        let index = self.index(from.0, from.1)?;
+        self.index(to.0, to.1)?;
+
+        if self.board.fields[index].owner != Owner::Me {
+            return Err(ActionError::NotOwned);
+        }
+        if amount == 0 {
+            return Err(ActionError::ZeroAmount);
+        }
+        if self.movable_units[index] == 0 {
+            return Err(ActionError::NoUnitsLeft);
+        }
+        if self.board.next_step(from, to).is_none() {
+            return Err(ActionError::Unreachable);
+        }
+
+        self.movable_units[index] -= amount.min(self.movable_units[index]);
+        Ok(())
+    }
+
+    fn validate_build(&mut self, x: u32, y: u32) -> Result<(), ActionError> {
+        let index = self.index(x, y)?;
+        let field = &self.board.fields[index];
+
+        if field.owner != Owner::Me {
+            return Err(ActionError::NotOwned);
+        }
+        if field.has_recycler {
+            return Err(ActionError::RecyclerPresent);
+        }
+        if !field.can_build {
+            return Err(ActionError::CannotBuild);
+        }
+        self.pay(RECYCLER_COST)?;
+
+        // Robots can no longer move onto the field, and nothing can be spawned there
+        self.board.fields[index].has_recycler = true;
+        self.board.fields[index].can_build = false;
+        self.board.fields[index].can_spawn = false;
+        Ok(())
+    }
+
+    fn validate_spawn(&mut self, amount: u32, x: u32, y: u32) -> Result<(), ActionError> {
+        let index = self.index(x, y)?;
+        let field = &self.board.fields[index];
+
+        if amount == 0 {
+            return Err(ActionError::ZeroAmount);
+        }
+        if field.owner != Owner::Me {
+            return Err(ActionError::NotOwned);
+        }
+        if field.has_recycler {
+            return Err(ActionError::RecyclerPresent);
+        }
+        if !field.can_spawn {
+            return Err(ActionError::CannotSpawn);
+        }
+        self.pay(amount * ROBOT_COST)
+    }
+
+    fn pay(&mut self, needed: u32) -> Result<(), ActionError> {
+        if needed > self.matter {
+            return Err(ActionError::NotEnoughMatter { needed, available: self.matter });
+        }
+
+        self.matter -= needed;
+        Ok(())
+    }
+
+    fn index(&self, x: u32, y: u32) -> Result<usize, ActionError> {
+        if x < self.board.width && y < self.board.height {
+            Ok((x + y * self.board.width) as usize)
+        } else {
+            Err(ActionError::OutOfBounds)
+        }
+    }
+}
+
+impl Action {
+    /// Checks a single action against the board, as if it were the only action this turn
+    ///
+    /// Use a `TurnValidator` to check all actions of a turn together.
+    pub fn validate(&self, board: &Board) -> Result<(), ActionError> {
+        TurnValidator::new(board).validate(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> Board {
+        let mut board = Board::from_ascii("
+            my_matter 20
+            5m2 5mR 5  . 5
+            5m  5m  5o 5 5o1
+        ").unwrap();
+        // grass we still own, until the end of the turn
+        board.fields[3].owner = Owner::Me;
+        board.update_build_spawn_flags();
+        board
+    }
+
+    #[test]
+    fn moves_need_units_on_an_owned_field_and_a_path() {
+        let mut validator = TurnValidator::new(&board());
+
+        assert_eq!(validator.validate(&Action::Move { amount: 1, from: (0, 0), to: (5, 0) }), Err(ActionError::OutOfBounds));
+        assert_eq!(validator.validate(&Action::Move { amount: 1, from: (4, 1), to: (3, 1) }), Err(ActionError::NotOwned));
+        assert_eq!(validator.validate(&Action::Move { amount: 0, from: (0, 0), to: (0, 1) }), Err(ActionError::ZeroAmount));
+        assert_eq!(validator.validate(&Action::Move { amount: 1, from: (0, 0), to: (0, 0) }), Err(ActionError::Unreachable));
+        assert_eq!(validator.validate(&Action::Move { amount: 1, from: (0, 0), to: (2, 0) }), Ok(()));
+        // more than the one unit that is left moves that unit, then there are none left
+        assert_eq!(validator.validate(&Action::Move { amount: 3, from: (0, 0), to: (0, 1) }), Ok(()));
+        assert_eq!(validator.validate(&Action::Move { amount: 1, from: (0, 0), to: (0, 1) }), Err(ActionError::NoUnitsLeft));
+    }
+
+    #[test]
+    fn builds_and_spawns_need_a_free_owned_field_and_matter() {
+        let mut validator = TurnValidator::new(&board());
+
+        assert_eq!(validator.validate(&Action::Build(2, 1)), Err(ActionError::NotOwned));
+        assert_eq!(validator.validate(&Action::Build(1, 0)), Err(ActionError::RecyclerPresent));
+        assert_eq!(validator.validate(&Action::Spawn(1, 1, 0)), Err(ActionError::RecyclerPresent));
+        assert_eq!(validator.validate(&Action::Build(0, 0)), Err(ActionError::CannotBuild));
+        assert_eq!(validator.validate(&Action::Build(3, 0)), Err(ActionError::CannotBuild));
+        assert_eq!(validator.validate(&Action::Spawn(1, 3, 0)), Err(ActionError::CannotSpawn));
+        assert_eq!(validator.validate(&Action::Spawn(0, 0, 1)), Err(ActionError::ZeroAmount));
+        assert_eq!(validator.validate(&Action::Spawn(3, 0, 1)), Err(ActionError::NotEnoughMatter { needed: 30, available: 20 }));
+        assert_eq!(validator.validate(&Action::Build(0, 1)), Ok(()));
+        assert_eq!(validator.validate(&Action::Spawn(2, 1, 1)), Err(ActionError::NotEnoughMatter { needed: 20, available: 10 }));
+    }
+
+    #[test]
+    fn builds_come_before_spawns_and_moves() {
+        let actions = [
+            Action::Spawn(1, 1, 1),
+            Action::Move { amount: 1, from: (0, 0), to: (0, 1) },
+            Action::Build(1, 1),
+        ];
+
+        assert_eq!(TurnValidator::new(&board()).validate_turn(&actions), vec![
+            Err(ActionError::RecyclerPresent),
+            Ok(()),
+            Ok(()),
+        ]);
+    }
+}
@@ -0,0 +1,173 @@
+//! Beam search over our own plans for the next few turns
+//!
+//! Every state of the beam is expanded with the distinct candidate turns of `Plan`, played against a
+//! fixed `OpponentModel`, and only the best `width` states are kept for the next depth.
+//! The first turn of the best state at the deepest level reached is played.
+//! Nothing is random, but the depth reached depends on the clock; with a `max_expansions` budget
+//! that runs out before the deadline, the same board always gives the same turn.
+
+use std::time::{Duration, Instant};
+use super::super::action::Action;
+use super::super::board::distance_board::DistanceSearch;
+use super::super::board::{Board, Owner};
+use super::super::evaluation::{Evaluator, RobotDifference, TerritoryDifference, WeightedBlend};
+use super::mcts::Plan;
+use super::simple_economy::SimpleEconomyAgent;
+use super::{Agent, TurnContext, TURN_TIME};
+
+/// What the opponent is assumed to play on every turn of the search
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OpponentModel {
+    /// No actions at all, so robots stay where they are and nothing is spawned or built
+    StandStill,
+    /// The turn `SimpleEconomyAgent` would play in the opponent's place
+    SimpleEconomy,
+}
+
+pub struct BeamSearchAgent {
+    /// The number of states kept at every depth
+    pub width: usize,
+    /// The number of turns played forward
+    pub depth: u32,
+    /// Stops the search after this many expanded states, dropping the depth it is in like at the deadline;
+    /// for tests and tuning that need to be reproducible
+    pub max_expansions: Option<u32>,
+    pub opponent_model: OpponentModel,
+    /// Scores the boards of every depth, to pick the states to keep
+    pub evaluator: Box<dyn Evaluator>,
+    pub distance_search: DistanceSearch,
+    opponent: SimpleEconomyAgent,
+}
+
+impl Default for BeamSearchAgent {
+    fn default() -> Self {
+        BeamSearchAgent {
+            width: 4,
+            depth: 5,
+            max_expansions: None,
+            opponent_model: OpponentModel::SimpleEconomy,
+            evaluator: Box::new(WeightedBlend::default().with(1.0, TerritoryDifference).with(0.5, RobotDifference)),
+            distance_search: DistanceSearch::default(),
+            opponent: SimpleEconomyAgent::default(),
+        }
+    }
+}
+
+/// A board reached by the search, with the turn we play now to get there
+struct State {
+    board: Board,
+    first_turn: Vec<Action>,
+    score: f32,
+}
+
+impl BeamSearchAgent {
+
+    /// Searches `board` until the full depth is done or `deadline` is near, and returns the turn to play
+    ///
+    /// A depth that can't be finished in time is dropped, the deepest finished one picks the turn.
+    pub fn search(&mut self, board: &Board, deadline: Instant) -> Vec<Action> {
+        let mut beam = vec![State { board: board.clone(), first_turn: Vec::new(), score: 0.0 }];
+
+        // the one state of depth 0 is always expanded, after that a state is only expanded
+        // if the slowest expansion so far would still finish before the deadline
+        let mut slowest = Duration::ZERO;
+        let mut expansions = 0;
+        'search: for depth in 0..self.depth {
+            let mut next = Vec::new();
+            for state in beam.iter() {
+                let start = Instant::now();
+                if depth > 0 && (start + slowest >= deadline || self.max_expansions.is_some_and(|x| expansions >= x)) {
+                    break 'search;
+                }
+
+                let opponent_actions = self.opponent_actions(&state.board);
+                let candidates = Plan::candidates(&state.board, &mut self.distance_search);
+                for (i, actions) in candidates.iter().enumerate() {
+                    // plans often come out the same, like building without matter or moving without robots,
+                    // and copies of a state would crowd the others out of the beam
+                    if candidates[..i].contains(actions) {
+                        continue;
+                    }
+
+                    let board = state.board.apply_turn(actions, &opponent_actions);
+                    let first_turn = if depth == 0 { actions.clone() } else { state.first_turn.clone() };
+                    next.push(State { score: self.evaluator.evaluate(&board, Owner::Me), board, first_turn });
+                }
+                slowest = slowest.max(start.elapsed());
+                expansions += 1;
+            }
+
+            // a stable sort keeps the candidate order for ties
+            next.sort_by(|a, b| b.score.total_cmp(&a.score));
+            next.truncate(self.width);
+            beam = next;
+        }
+
+        beam.swap_remove(0).first_turn
+    }
+
+    fn opponent_actions(&mut self, board: &Board) -> Vec<Action> {
+        match self.opponent_model {
+            OpponentModel::StandStill => Vec::new(),
+            OpponentModel::SimpleEconomy => self.opponent.generate_actions(board.swap_perspective()),
+        }
+    }
+}
+
+impl Agent for BeamSearchAgent {
+    fn generate_actions(&mut self, board: Board) -> Vec<Action> {
+        self.search(&board, Instant::now() + TURN_TIME)
+    }
+
+    fn act(&mut self, board: &Board, context: &TurnContext) -> Vec<Action> {
+        self.search(board, context.deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_free_fields_against_an_opponent_that_stands_still() {
+        let board = Board::from_ascii("
+            5m2 5 5 5 . 5o1
+            5m  5 5 5 . 5o
+        ").unwrap();
+        let mut agent = BeamSearchAgent { opponent_model: OpponentModel::StandStill, ..Default::default() };
+
+        let actions = agent.search(&board, Instant::now() + Duration::from_secs(5));
+        assert_eq!(actions, vec![Action::Move { amount: 2, from: (0, 0), to: (1, 0) }]);
+    }
+
+    #[test]
+    fn same_board_same_turn() {
+        let board = Board::from_ascii("
+            5m1 5 5  5 5o1
+            5m  5 9 5 5o
+            5   5 5 5 5
+        ").unwrap();
+        let board = Board { my_matter: 30, opponent_matter: 30, ..board };
+        let agent = || BeamSearchAgent { depth: 100, max_expansions: Some(30), ..Default::default() };
+
+        let first = agent().search(&board, Instant::now() + Duration::from_secs(5));
+        let second = agent().search(&board, Instant::now() + Duration::from_secs(5));
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn search_returns_before_a_short_deadline() {
+        let board = Board::from_ascii("
+            5m1 5 5 9 5 5 5o1
+            5m  5 9 9 9 5 5o
+            5m  5 5 9 5 5 5o
+        ").unwrap();
+        let board = Board { my_matter: 20, opponent_matter: 20, ..board };
+        let mut agent = BeamSearchAgent { width: 8, depth: 100, ..Default::default() };
+
+        let deadline = Instant::now() + Duration::from_millis(20);
+        assert!(!agent.search(&board, deadline).is_empty());
+        assert!(Instant::now() < deadline);
+    }
+}
@@ -0,0 +1,235 @@
+//! An agent put together from separate policies for moving, building and spawning
+//!
+//! Every turn the overlays are computed once and shared by all policies. They are asked for moves,
+//! then builds, then spawns, although the referee plays the builds before the moves. Builds and
+//! spawns share one matter budget, so a policy only gets the matter the policies before it left,
+//! and it sees the board with the recyclers they built.
+
+use super::super::action::Action;
+use super::super::board::distance_board::{DistanceBoard, DistanceSearch};
+use super::super::board::mine_duration_board::MineDurationBoard;
+use super::super::board::simulation::{RECYCLER_COST, ROBOT_COST};
+use super::super::board::territory_board::TerritoryBoard;
+use super::super::board::yield_board::YieldBoard;
+use super::super::board::{Board, Owner};
+use super::simple_economy::{RecyclerPlacement, SimpleEconomyAgent};
+use super::Agent;
+
+pub trait MovePolicy {
+    /// The moves for our robots
+    fn moves(&mut self, board: &Board, overlays: &Overlays, search: &mut DistanceSearch) -> Vec<Action>;
+}
+
+pub trait BuildPolicy {
+    /// The recyclers to build, costing no more than `budget`
+    fn builds(&mut self, board: &Board, overlays: &Overlays, budget: &MatterBudget) -> Vec<Action>;
+}
+
+pub trait SpawnPolicy {
+    /// The robots to spawn, costing no more than `budget`
+    fn spawns(&mut self, board: &Board, overlays: &Overlays, budget: &MatterBudget) -> Vec<Action>;
+}
+
+/// The boards computed once a turn for all policies
+pub struct Overlays {
+    /// The board the turn started with, without the recyclers the build policies add
+    pub board: Board,
+    /// Yields, ignoring all recyclers
+    pub expected_mining: YieldBoard,
+    /// Yields, accounting for the current recyclers
+    pub prospective_mining: YieldBoard,
+    pub territory_board: TerritoryBoard,
+    /// When fields turn to grass
+    pub mine_duration_board: MineDurationBoard,
+    /// Our distances, only over fields that are still there when our robots get to them
+    pub reachable_board: DistanceBoard,
+    pub opponent_distance_board: DistanceBoard,
+    pub neutral_distance_board: DistanceBoard,
+}
+
+impl Overlays {
+
+    pub fn new(board: &Board, search: &mut DistanceSearch) -> Self {
+        let mine_duration_board = MineDurationBoard::new(board);
+        let reachable_board = search.with_grass_forecast(board, Owner::Me, &mine_duration_board);
+        let opponent_distance_board = search.through(board, Owner::Opponent, &reachable_board);
+        let neutral_distance_board = search.through(board, Owner::Neutral, &reachable_board);
+
+        Self {
+            board: board.clone(),
+            expected_mining: YieldBoard::without_recycling(board),
+            prospective_mining: YieldBoard::with_recycling(board),
+            territory_board: TerritoryBoard::new(board),
+            mine_duration_board,
+            reachable_board,
+            opponent_distance_board,
+            neutral_distance_board,
+        }
+    }
+
+    /// Hands the distance boards back to `search`, for the next turn to reuse
+    pub fn recycle(self, search: &mut DistanceSearch) {
+        search.recycle(self.reachable_board);
+        search.recycle(self.opponent_distance_board);
+        search.recycle(self.neutral_distance_board);
+    }
+}
+
+/// The matter left to spend this turn
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MatterBudget {
+    pub matter: u32,
+}
+
+impl MatterBudget {
+
+    pub fn recyclers(&self) -> u32 {
+        self.matter / RECYCLER_COST
+    }
+
+    pub fn robots(&self) -> u32 {
+        self.matter / ROBOT_COST
+    }
+
+    /// Takes what `actions` cost out of the budget
+    pub fn spend(&mut self, actions: &[Action]) {
+        for action in actions {
+            let cost = match action {
+                Action::Build(..) => RECYCLER_COST,
+                Action::Spawn(amount, ..) => amount * ROBOT_COST,
+                _ => 0,
+            };
+            self.matter = self.matter.saturating_sub(cost);
+        }
+    }
+}
+
+pub struct CompositeAgent {
+    pub move_policy: Box<dyn MovePolicy>,
+    /// Asked in order, each with the matter the ones before left
+    pub build_policies: Vec<Box<dyn BuildPolicy>>,
+    pub spawn_policy: Box<dyn SpawnPolicy>,
+    pub distance_search: DistanceSearch,
+}
+
+/// Plays like `SimpleEconomyAgent` with its default settings
+impl Default for CompositeAgent {
+    fn default() -> Self {
+        Self::from(&SimpleEconomyAgent::default())
+    }
+}
+
+/// The policies `agent` plays with, to swap some of them out
+impl From<&SimpleEconomyAgent> for CompositeAgent {
+    fn from(agent: &SimpleEconomyAgent) -> Self {
+        let move_policy: Box<dyn MovePolicy> = match agent.move_policy {
+            Some(policy) => Box::new(policy),
+            None => Box::new(agent.greedy_move_policy()),
+        };
+        let mut build_policies: Vec<Box<dyn BuildPolicy>> = Vec::new();
+        if agent.recycler_placement == RecyclerPlacement::Chokepoint {
+            build_policies.push(Box::new(agent.chokepoint_build_policy()));
+        }
+        build_policies.push(Box::new(agent.yield_build_policy()));
+
+        Self {
+            move_policy,
+            build_policies,
+            spawn_policy: Box::new(agent.front_spawn_policy()),
+            distance_search: DistanceSearch::default(),
+        }
+    }
+}
+
+impl Agent for CompositeAgent {
+    fn generate_actions(&mut self, board: Board) -> Vec<Action> {
+        let overlays = Overlays::new(&board, &mut self.distance_search);
+        let mut budget = MatterBudget { matter: board.my_matter };
+
+        let mut result = self.move_policy.moves(&board, &overlays, &mut self.distance_search);
+
+        // the policies after a build see its recycler, so they don't build or spawn on the same field
+        let mut built = board.clone();
+        for policy in self.build_policies.iter_mut() {
+            let builds = policy.builds(&built, &overlays, &budget);
+            budget.spend(&builds);
+            built.apply_builds(&builds, Owner::Me);
+            built.update_build_spawn_flags();
+            result.extend(builds);
+        }
+        result.extend(self.spawn_policy.spawns(&built, &overlays, &budget));
+
+        overlays.recycle(&mut self.distance_search);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The turn of a `SimpleEconomyAgent` with the given placement; `eager` builds wherever a recycler scores at all
+    fn turn(board: &Board, placement: RecyclerPlacement, eager: bool) -> Vec<Action> {
+        let agent = if eager {
+            SimpleEconomyAgent { recycler_placement: placement, recycler_min_score: 0, chokepoint_min_territory: 1, ..Default::default() }
+        } else {
+            SimpleEconomyAgent { recycler_placement: placement, ..Default::default() }
+        };
+        CompositeAgent::from(&agent).generate_actions(board.clone())
+    }
+
+    // the expected turns were recorded from SimpleEconomyAgent before it was split into policies
+    #[test]
+    fn plays_the_turns_of_the_agent_it_was_made_from() {
+        let board = Board::from_ascii("
+            my_matter 40
+            opponent_matter 40
+            5m1 5m 5 9 5 5o  5o1
+            5m  5  5 . 5 5   5o
+            5   5  7 5 . 5oR 5o
+        ").unwrap();
+        let advance = Action::Move { amount: 1, from: (0, 0), to: (1, 0) };
+
+        for placement in [RecyclerPlacement::Yield, RecyclerPlacement::Chokepoint] {
+            assert_eq!(turn(&board, placement, false), vec![advance.clone(), Action::Spawn(3, 1, 0), Action::Spawn(1, 0, 1)]);
+        }
+        assert_eq!(turn(&board, RecyclerPlacement::Chokepoint, true), vec![advance, Action::Build(1, 0), Action::Spawn(3, 0, 1)]);
+    }
+
+    #[test]
+    fn builds_for_yield_after_a_chokepoint_like_the_agent_it_was_made_from() {
+        // we're just behind, and the recycler on the chokepoint doesn't change that for this turn
+        let board = Board::from_ascii("
+            my_matter 100
+            opponent_matter 100
+            9m 9m 9m 5m1 5 5 5o1 5o 5o
+            9m 9m 9m 5m  5 5 5o  5o 5o
+            9m 9m 9m 5m1 5 5 5o1 5o 5o
+        ").unwrap();
+        let moves = [Action::Move { amount: 1, from: (3, 0), to: (4, 0) }, Action::Move { amount: 1, from: (3, 2), to: (4, 2) }];
+        let yield_builds = [(1, 1), (1, 0), (1, 2), (2, 1), (2, 0), (2, 2)].map(|(x, y)| Action::Build(x, y));
+
+        assert_eq!(turn(&board, RecyclerPlacement::Yield, true), [
+            &moves[..],
+            &yield_builds,
+            &[Action::Spawn(2, 3, 1), Action::Spawn(1, 3, 0), Action::Spawn(1, 3, 2)],
+        ].concat());
+        assert_eq!(turn(&board, RecyclerPlacement::Chokepoint, true), [
+            &moves[..],
+            &[Action::Build(3, 1)],
+            &yield_builds,
+            &[Action::Spawn(2, 3, 0), Action::Spawn(1, 3, 2)],
+        ].concat());
+    }
+
+    #[test]
+    fn spends_no_more_than_the_budget() {
+        let mut budget = MatterBudget { matter: 45 };
+        budget.spend(&[Action::Build(0, 0), Action::Spawn(2, 1, 0), Action::Move { amount: 3, from: (1, 0), to: (2, 0) }]);
+        assert_eq!(budget, MatterBudget { matter: 15 });
+        assert_eq!(budget.robots(), 1);
+
+        budget.spend(&[Action::Spawn(2, 1, 0)]);
+        assert_eq!(budget.recyclers(), 0);
+    }
+}
@@ -0,0 +1,450 @@
+//! Simultaneous-move Monte Carlo tree search over whole turns
+//!
+//! Every node of the tree holds a board and a handful of candidate turns for both players,
+//! built from a `Plan` instead of from every way to split the robots.
+//! Both players pick their candidate on their own (decoupled UCT), the pair of turns is simulated
+//! to get the next node, and a rollout of random plans scores every new node.
+
+use std::time::{Duration, Instant};
+use super::super::action::Action;
+use super::super::board::distance_board::{DistanceBoard, DistanceSearch};
+use super::super::board::simulation::{RECYCLER_COST, ROBOT_COST};
+use super::super::board::mine_duration_board::MineDurationBoard;
+use super::super::board::yield_board::YieldBoard;
+use super::super::board::{Board, Owner};
+use super::super::evaluation::{Evaluator, RobotDifference, TerritoryDifference, WeightedBlend};
+use super::super::rng::Rng;
+use super::simple_economy::SimpleEconomyAgent;
+use super::{Agent, TurnContext, TURN_TIME};
+
+/// Where the robots of a plan go
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MovePlan {
+    /// Towards the nearest opponent field, or the nearest neutral one when no opponent is reachable
+    Advance,
+    /// Towards the nearest neutral field, or the nearest opponent one when none is left
+    Expand,
+    /// Nowhere
+    Hold,
+}
+
+/// A candidate turn: robots follow `moves`, matter goes into one recycler if `build`, the rest into robots at the front
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Plan {
+    pub moves: MovePlan,
+    pub build: bool,
+}
+
+impl Plan {
+
+    pub const ALL: [Plan; 6] = [
+        Plan { moves: MovePlan::Advance, build: false },
+        Plan { moves: MovePlan::Expand, build: false },
+        Plan { moves: MovePlan::Hold, build: false },
+        Plan { moves: MovePlan::Advance, build: true },
+        Plan { moves: MovePlan::Expand, build: true },
+        Plan { moves: MovePlan::Hold, build: true },
+    ];
+
+    /// The actions of the plan for the player that `board` is seen by
+    pub fn actions(self, board: &Board, search: &mut DistanceSearch) -> Vec<Action> {
+        let overlays = PlanOverlays::new(board, search);
+        let actions = self.actions_with(board, &overlays);
+        overlays.recycle(search);
+        actions
+    }
+
+    /// The actions of every plan in `ALL`, sharing the overlays
+    pub fn candidates(board: &Board, search: &mut DistanceSearch) -> Vec<Vec<Action>> {
+        let overlays = PlanOverlays::new(board, search);
+        let candidates = Plan::ALL
+            .iter()
+            .map(|x| x.actions_with(board, &overlays))
+            .collect();
+        overlays.recycle(search);
+        candidates
+    }
+
+    fn actions_with(self, board: &Board, overlays: &PlanOverlays) -> Vec<Action> {
+        let PlanOverlays { opponent_distance_board, neutral_distance_board, mine_duration_board, yield_board } = overlays;
+        let mut result = Vec::new();
+
+        for field in board.fields.iter().filter(|x| x.owner == Owner::Me && x.num_units > 0) {
+            let (first, second) = match self.moves {
+                MovePlan::Advance => (opponent_distance_board, neutral_distance_board),
+                MovePlan::Expand => (neutral_distance_board, opponent_distance_board),
+                MovePlan::Hold => continue,
+            };
+            let distance_board = if first.distances[field.coord()].is_unreachable() { second } else { first };
+
+            let mut steps = distance_board
+                .next_steps(field.coord())
+                .into_iter()
+                .filter(|c| mine_duration_board.is_safe_after(c.x, c.y, 1))
+                .collect::<Vec<_>>();
+            steps.sort_by_key(|c| board.fields[c.index(board.width)].owner == Owner::Me);
+
+            // split the stack evenly, the fields we don't own yet get the remainder
+            let step_count = steps.len() as u32;
+            for (i, to) in steps.into_iter().enumerate() {
+                let amount = field.num_units / step_count + u32::from((i as u32) < field.num_units % step_count);
+                if amount > 0 {
+                    result.push(Action::Move { amount, from: (field.x, field.y), to: to.into() });
+                }
+            }
+        }
+
+        let mut matter = board.my_matter;
+        let mut built = None;
+        if self.build && matter >= RECYCLER_COST {
+            built = board.fields
+                .iter()
+                .filter(|x| x.can_build && mine_duration_board.is_safe_after(x.x, x.y, 1))
+                .max_by_key(|x| yield_board.prospective_scrap[x.coord()])
+                .map(|x| x.coord());
+            if let Some(coord) = built {
+                result.push(Action::Build(coord.x, coord.y));
+                matter -= RECYCLER_COST;
+            }
+        }
+
+        // robots go to the owned fields next to fields we don't own, closest to the opponent first
+        let mut front = board.fields
+            .iter()
+            .filter(|x| x.can_spawn && mine_duration_board.is_safe_after(x.x, x.y, 1) && Some(x.coord()) != built)
+            .filter(|x| board.get_adjacent_fields(x.x, x.y)
+                .into_iter()
+                .flatten()
+                .any(|a| a.owner != Owner::Me && a.is_traversible()))
+            .map(|x| (opponent_distance_board.distances[x.coord()], x.coord()))
+            .collect::<Vec<_>>();
+        front.sort();
+        front.truncate(FRONT_SPAWN_FIELDS);
+
+        let robots = matter / ROBOT_COST;
+        let front_count = front.len() as u32;
+        for (i, (_, coord)) in front.into_iter().enumerate() {
+            let amount = robots / front_count + u32::from((i as u32) < robots % front_count);
+            if amount > 0 {
+                result.push(Action::Spawn(amount, coord.x, coord.y));
+            }
+        }
+
+        result
+    }
+}
+
+/// The number of fields a plan spreads its new robots over
+const FRONT_SPAWN_FIELDS: usize = 3;
+
+/// The boards a plan needs, computed once for all plans on a board
+struct PlanOverlays {
+    opponent_distance_board: DistanceBoard,
+    neutral_distance_board: DistanceBoard,
+    mine_duration_board: MineDurationBoard,
+    /// The scrap a recycler would mine, ignoring other recyclers
+    yield_board: YieldBoard,
+}
+
+impl PlanOverlays {
+
+    fn new(board: &Board, search: &mut DistanceSearch) -> Self {
+        Self {
+            opponent_distance_board: search.from_owner(board, Owner::Opponent),
+            neutral_distance_board: search.from_owner(board, Owner::Neutral),
+            mine_duration_board: MineDurationBoard::new(board),
+            yield_board: YieldBoard::without_recycling(board),
+        }
+    }
+
+    fn recycle(self, search: &mut DistanceSearch) {
+        search.recycle(self.opponent_distance_board);
+        search.recycle(self.neutral_distance_board);
+    }
+}
+
+/// Searches with decoupled UCT until the deadline of the turn, and plays the most visited candidate
+pub struct MctsAgent {
+    /// The exploration constant of UCB1
+    pub exploration: f32,
+    /// The number of turns of random plans played out from every new node
+    pub rollout_depth: u32,
+    /// Stops the search early; for tests and benchmarks that need to be reproducible
+    pub max_iterations: Option<u32>,
+    pub rng: Rng,
+    /// Scores the boards at the end of the rollouts; the score is squashed into a chance of winning
+    pub evaluator: Box<dyn Evaluator>,
+    /// Adds the turn `SimpleEconomyAgent` would play to the candidates at the root, for both players
+    pub seed_agent: Option<SimpleEconomyAgent>,
+    pub distance_search: DistanceSearch,
+    /// The nodes of the last search, kept until the next one so the turn doesn't wait for them to be freed
+    tree: Vec<Node>,
+    /// The longest a turn of a rollout took in this search, the time left must fit another one
+    slowest_turn: Duration,
+}
+
+impl Default for MctsAgent {
+    fn default() -> Self {
+        MctsAgent {
+            exploration: 0.7,
+            rollout_depth: 4,
+            max_iterations: None,
+            rng: Rng::new(0x6d63_7473),
+            evaluator: Box::new(WeightedBlend::default().with(1.0, TerritoryDifference).with(0.5, RobotDifference)),
+            seed_agent: Some(SimpleEconomyAgent::default()),
+            distance_search: DistanceSearch::default(),
+            tree: Vec::new(),
+            slowest_turn: Duration::ZERO,
+        }
+    }
+}
+
+/// Kept free before the deadline for picking the turn once the search stops, and for a late wake-up
+const RETURN_TIME: Duration = Duration::from_millis(1);
+
+/// Visits and total reward of one candidate turn of one player at a node
+#[derive(Copy, Clone, Debug, Default)]
+struct Arm {
+    visits: u32,
+    reward: f32,
+}
+
+struct Node {
+    board: Board,
+    /// The candidate turns of us and of the opponent
+    candidates: [Vec<Vec<Action>>; 2],
+    arms: [Vec<Arm>; 2],
+    visits: u32,
+    /// The node every pair of candidates leads to, by `mine * their count + theirs`
+    children: Vec<Option<usize>>,
+}
+
+impl MctsAgent {
+
+    /// Plays out the search on `board` and returns our most visited candidate
+    ///
+    /// Every step of the search only starts while the slowest one of its kind so far still fits
+    /// before `RETURN_TIME` ahead of `deadline`, and rollouts that run out of time are scored where they stopped.
+    pub fn search(&mut self, board: &Board, deadline: Instant) -> Vec<Action> {
+        // the tree of the last search is dropped here, where the loop below still accounts for the time it takes
+        self.tree.clear();
+        self.slowest_turn = Duration::ZERO;
+        let stop = deadline - RETURN_TIME;
+
+        let mut root = self.new_node(board.clone());
+        if let Some(agent) = self.seed_agent.as_mut() {
+            let mut slowest = Duration::ZERO;
+            for (player, seen) in [board.clone(), board.swap_perspective()].into_iter().enumerate() {
+                let start = Instant::now();
+                if start + slowest >= stop {
+                    break;
+                }
+                root.candidates[player].push(agent.generate_actions(seen));
+                root.arms[player].push(Arm::default());
+                slowest = slowest.max(start.elapsed());
+            }
+            root.children = vec![None; root.candidates[0].len() * root.candidates[1].len()];
+        }
+        let mut nodes = std::mem::take(&mut self.tree);
+        nodes.push(root);
+
+        let mut slowest = Duration::ZERO;
+        let mut iterations = 0;
+        while self.max_iterations.is_none_or(|x| iterations < x) && Instant::now() + slowest < stop {
+            let start = Instant::now();
+            self.iterate(&mut nodes, stop);
+            slowest = slowest.max(start.elapsed());
+            iterations += 1;
+        }
+
+        let best = nodes[0].arms[0]
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, arm)| arm.visits)
+            .map_or(0, |(i, _)| i);
+        let actions = nodes[0].candidates[0][best].clone();
+        self.tree = nodes;
+        actions
+    }
+
+    fn new_node(&mut self, board: Board) -> Node {
+        let opponent_board = board.swap_perspective();
+        let candidates = [
+            Plan::candidates(&board, &mut self.distance_search),
+            Plan::candidates(&opponent_board, &mut self.distance_search),
+        ];
+
+        Node {
+            board,
+            arms: [vec![Arm::default(); candidates[0].len()], vec![Arm::default(); candidates[1].len()]],
+            children: vec![None; candidates[0].len() * candidates[1].len()],
+            candidates,
+            visits: 0,
+        }
+    }
+
+    /// Selects down to a new node, rolls it out, expands it and backs up the result
+    fn iterate(&mut self, nodes: &mut Vec<Node>, stop: Instant) {
+        let mut path = Vec::new();
+        let mut current = 0;
+        let reward = loop {
+            let node = &nodes[current];
+            if is_over(&node.board) {
+                break self.win_chance(&node.board);
+            }
+
+            let picks = [self.select(&node.arms[0], node.visits), self.select(&node.arms[1], node.visits)];
+            path.push((current, picks));
+            let child = picks[0] * node.arms[1].len() + picks[1];
+
+            match node.children[child] {
+                Some(next) => current = next,
+                None => {
+                    let board = node.board.apply_turn(&node.candidates[0][picks[0]], &node.candidates[1][picks[1]]);
+                    let reward = self.rollout(board.clone(), stop);
+                    // a new node plans for both players like a rollout turn, and isn't worth it when the search is over
+                    if Instant::now() + self.slowest_turn < stop {
+                        let next = self.new_node(board);
+                        nodes.push(next);
+                        let index = nodes.len() - 1;
+                        nodes[current].children[child] = Some(index);
+                    }
+                    break reward;
+                },
+            }
+        };
+
+        for (node, picks) in path {
+            let node = &mut nodes[node];
+            node.visits += 1;
+            node.arms[0][picks[0]].visits += 1;
+            node.arms[0][picks[0]].reward += reward;
+            node.arms[1][picks[1]].visits += 1;
+            node.arms[1][picks[1]].reward += 1.0 - reward;
+        }
+    }
+
+    /// UCB1 over the arms of one player, trying every arm once first
+    fn select(&mut self, arms: &[Arm], visits: u32) -> usize {
+        if let Some(unvisited) = arms.iter().position(|x| x.visits == 0) {
+            return unvisited;
+        }
+
+        let log_visits = (visits.max(1) as f32).ln();
+        let ucb = |arm: &Arm| arm.reward / arm.visits as f32 + self.exploration * (log_visits / arm.visits as f32).sqrt();
+        let mut best = 0;
+        for (i, arm) in arms.iter().enumerate() {
+            // ties are broken at random, so equal candidates share the visits
+            if ucb(arm) > ucb(&arms[best]) || (ucb(arm) == ucb(&arms[best]) && self.rng.range(0, 1) == 0) {
+                best = i;
+            }
+        }
+        best
+    }
+
+    /// Both players follow random plans for `rollout_depth` turns, or as many as fit before `stop`
+    fn rollout(&mut self, mut board: Board, stop: Instant) -> f32 {
+        for _ in 0..self.rollout_depth {
+            let start = Instant::now();
+            if is_over(&board) || start + self.slowest_turn >= stop {
+                break;
+            }
+
+            let mine = Plan::ALL[self.rng.range(0, Plan::ALL.len() as u32 - 1) as usize];
+            let theirs = Plan::ALL[self.rng.range(0, Plan::ALL.len() as u32 - 1) as usize];
+            let my_actions = mine.actions(&board, &mut self.distance_search);
+            let opponent_actions = theirs.actions(&board.swap_perspective(), &mut self.distance_search);
+            board = board.apply_turn(&my_actions, &opponent_actions);
+            self.slowest_turn = self.slowest_turn.max(start.elapsed());
+        }
+
+        self.win_chance(&board)
+    }
+
+    /// Our chance of winning from `board`, guessed from the evaluator
+    fn win_chance(&self, board: &Board) -> f32 {
+        0.5 + 0.5 * (self.evaluator.evaluate(board, Owner::Me) / 8.0).tanh()
+    }
+}
+
+impl Agent for MctsAgent {
+    fn generate_actions(&mut self, board: Board) -> Vec<Action> {
+        self.search(&board, Instant::now() + TURN_TIME)
+    }
+
+    fn act(&mut self, board: &Board, context: &TurnContext) -> Vec<Action> {
+        self.search(board, context.deadline)
+    }
+}
+
+/// The game ends when a player has no fields left
+fn is_over(board: &Board) -> bool {
+    board.territory_count(Owner::Me) == 0 || board.territory_count(Owner::Opponent) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::board::map_generator::MapGenerator;
+
+    #[test]
+    fn plans_split_robots_without_stepping_onto_grass() {
+        let board = Board::from_ascii("
+            5m3 5   5o1
+            1   5mR 5o
+            5m  5   5
+        ").unwrap();
+
+        let hold = Plan { moves: MovePlan::Hold, build: false }.actions(&board, &mut DistanceSearch::default());
+        assert!(hold.iter().all(|x| !matches!(x, Action::Move { .. })));
+
+        // (0, 1) turns to grass in the recycler's next harvest, so all three go right
+        let expand = Plan { moves: MovePlan::Expand, build: false }.actions(&board, &mut DistanceSearch::default());
+        assert_eq!(expand, vec![Action::Move { amount: 3, from: (0, 0), to: (1, 0) }]);
+    }
+
+    #[test]
+    fn search_plays_a_valid_turn() {
+        let board = Board::from_ascii("
+            5m1 5 5 5 5o1
+            5m  5 5 5 5o
+        ").unwrap();
+        let board = Board { my_matter: 20, opponent_matter: 20, ..board };
+        let mut agent = MctsAgent { max_iterations: Some(200), ..Default::default() };
+
+        let actions = agent.search(&board, Instant::now() + Duration::from_secs(5));
+        assert!(!actions.is_empty());
+        assert!(actions.iter().all(|x| match x {
+            Action::Move { from, .. } => *from == (0, 0),
+            Action::Spawn(_, x, _) | Action::Build(x, _) => *x == 0,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn search_returns_before_a_short_deadline() {
+        let board = Board::from_ascii("
+            5m1 5 5 9 5 5 5o1
+            5m  5 9 9 9 5 5o
+            5m  5 5 9 5 5 5o
+        ").unwrap();
+        let board = Board { my_matter: 20, opponent_matter: 20, ..board };
+        let mut agent = MctsAgent::default();
+
+        for _ in 0..3 {
+            let deadline = Instant::now() + Duration::from_millis(20);
+            agent.search(&board, deadline);
+            assert!(Instant::now() < deadline);
+        }
+
+        // a full size map, where rollouts have to be cut short; the root and the first rollout turn
+        // have nothing to go by, so there is time for them even in a debug build
+        let board = MapGenerator::new(7).generate();
+        assert_eq!((board.width, board.height), (24, 12));
+        for _ in 0..3 {
+            let deadline = Instant::now() + Duration::from_millis(100);
+            agent.search(&board, deadline);
+            assert!(Instant::now() < deadline);
+        }
+    }
+}
@@ -1,11 +1,188 @@
+use std::time::{Duration, Instant};
 use super::board::Board;
 use super::action::Action;
+use super::inference::OpponentTurn;
 
 /// Every submodule is an agent implementation
-
 pub mod simple_economy;
+pub mod mcts;
+pub mod beam_search;
+pub mod composite;
 
+/// CodinGame allows 1000ms for the first turn; leave room for reading the input and writing the actions
+pub const FIRST_TURN_TIME: Duration = Duration::from_millis(950);
+/// CodinGame allows 50ms for every other turn
+pub const TURN_TIME: Duration = Duration::from_millis(45);
 
 pub trait Agent {
     fn generate_actions(&mut self, board: Board) -> Vec<Action>;
+
+    /// Called once, before the first turn is played
+    fn on_game_start(&mut self, _board: &Board) {}
+
+    /// Plays a turn knowing the rest of the game; agents that don't need it only implement `generate_actions`
+    fn act(&mut self, board: &Board, _context: &TurnContext) -> Vec<Action> {
+        self.generate_actions(board.clone())
+    }
+
+    /// Called once with the last board, when the game is over
+    fn on_game_end(&mut self, _board: &Board) {}
+}
+
+/// Everything an agent may want to know about a turn, besides the board
+#[derive(Copy, Clone, Debug)]
+pub struct TurnContext<'a> {
+    /// Turns are counted from 0
+    pub turn: u32,
+    pub deadline: Instant,
+    /// The boards of the earlier turns, oldest first
+    pub history: &'a [Board],
+    /// The actions we sent on the earlier turns, in the same order as `history`
+    pub previous_actions: &'a [Vec<Action>],
+}
+
+impl TurnContext<'_> {
+
+    pub fn time_left(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    pub fn previous_board(&self) -> Option<&Board> {
+        self.history.last()
+    }
+
+    /// The actions we sent on the previous turn
+    pub fn last_actions(&self) -> &[Action] {
+        self.previous_actions.last().map_or(&[], |x| x.as_slice())
+    }
+
+    /// What the opponent did on the previous turn, leading up to `board`
+    pub fn opponent_last_turn(&self, board: &Board) -> Option<OpponentTurn> {
+        self.previous_board().map(|previous| OpponentTurn::infer(previous, self.last_actions(), board))
+    }
+}
+
+/// Keeps the boards and our actions of a game, to hand out as `TurnContext`
+#[derive(Clone, Debug, Default)]
+pub struct TurnHistory {
+    pub boards: Vec<Board>,
+    pub actions: Vec<Vec<Action>>,
+}
+
+impl TurnHistory {
+
+    pub fn turn(&self) -> u32 {
+        self.boards.len() as u32
+    }
+
+    /// The time a turn starting now may take
+    pub fn deadline(&self, start: Instant) -> Instant {
+        start + if self.boards.is_empty() { FIRST_TURN_TIME } else { TURN_TIME }
+    }
+
+    pub fn context(&self, deadline: Instant) -> TurnContext<'_> {
+        TurnContext {
+            turn: self.turn(),
+            deadline,
+            history: &self.boards,
+            previous_actions: &self.actions,
+        }
+    }
+
+    /// Lets `agent` play `board`, calling `on_game_start` on the first turn
+    ///
+    /// The turn isn't recorded yet, as the caller may still drop some of the actions; see `push`.
+    pub fn play(&self, agent: &mut dyn Agent, board: &Board, start: Instant) -> Vec<Action> {
+        if self.boards.is_empty() {
+            agent.on_game_start(board);
+        }
+        agent.act(board, &self.context(self.deadline(start)))
+    }
+
+    /// Records a turn with the actions that were actually sent
+    pub fn push(&mut self, board: Board, actions: Vec<Action>) {
+        self.boards.push(board);
+        self.actions.push(actions);
+    }
+}
+
+/// Creates an agent with its default settings, used by the tools to pick an agent
+pub fn from_name(name: &str) -> Option<Box<dyn Agent>> {
+    match name {
+        "simple_economy" => Some(Box::<simple_economy::SimpleEconomyAgent>::default()),
+        "mcts" => Some(Box::<mcts::MctsAgent>::default()),
+        "beam_search" => Some(Box::<beam_search::BeamSearchAgent>::default()),
+        "composite" => Some(Box::<composite::CompositeAgent>::default()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Waits, and remembers what it was told
+    #[derive(Default)]
+    struct Recording {
+        turns: Vec<u32>,
+        time_left: Vec<Duration>,
+        started: u32,
+    }
+
+    impl Agent for Recording {
+        fn generate_actions(&mut self, _board: Board) -> Vec<Action> {
+            vec![Action::Wait]
+        }
+
+        fn on_game_start(&mut self, _board: &Board) {
+            self.started += 1;
+        }
+
+        fn act(&mut self, _board: &Board, context: &TurnContext) -> Vec<Action> {
+            self.turns.push(context.turn);
+            self.time_left.push(context.time_left());
+            vec![Action::Build(context.turn, 0)]
+        }
+    }
+
+    #[test]
+    fn the_first_turn_gets_more_time() {
+        let mut history = TurnHistory::default();
+        // deadlines count from the start passed in, not from now
+        let start = Instant::now() + Duration::from_secs(60);
+        let first = history.deadline(start) - start;
+        assert_eq!(history.deadline(start + Duration::from_secs(1)), history.deadline(start) + Duration::from_secs(1));
+
+        history.push(Board::from_ascii("5").unwrap(), Vec::new());
+        let later = history.deadline(start) - start;
+        assert!(later > Duration::ZERO && later < first);
+
+        history.push(Board::from_ascii("5").unwrap(), Vec::new());
+        assert_eq!(history.deadline(start) - start, later);
+    }
+
+    #[test]
+    fn history_pairs_boards_with_the_actions_sent() {
+        let boards = ["1 5m1", "2 5m1", "3 5m1"].map(|x| Board::from_ascii(x).unwrap());
+        let mut agent = Recording::default();
+        let mut history = TurnHistory::default();
+
+        for board in boards.iter() {
+            let actions = history.play(&mut agent, board, Instant::now());
+            history.push(board.clone(), actions);
+        }
+
+        assert_eq!(agent.started, 1);
+        assert_eq!(agent.turns, vec![0, 1, 2]);
+        assert!(agent.time_left[0] > TURN_TIME && agent.time_left[1] <= TURN_TIME);
+        assert_eq!(history.turn(), 3);
+        for (turn, (board, actions)) in history.boards.iter().zip(history.actions.iter()).enumerate() {
+            assert_eq!(board.fields[0].scrap_amount, turn as u32 + 1);
+            assert_eq!(actions, &vec![Action::Build(turn as u32, 0)]);
+        }
+
+        let context = history.context(Instant::now());
+        assert_eq!(context.previous_board().unwrap().fields[0].scrap_amount, 3);
+        assert_eq!(context.last_actions(), &[Action::Build(2, 0)]);
+    }
 }
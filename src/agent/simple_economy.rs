@@ -1,10 +1,18 @@
-use std::cmp::Ordering;
+use std::cmp::Reverse;
 use std::iter::zip;
-use super::super::board::adjacent_in_range;
+use super::super::board::{Coord, Grid};
+use super::super::board::render::{RenderMode, Renderer};
 use super::super::board::recycler_range_board::RecyclerRangeBoard;
 use super::super::board::yield_board::YieldBoard;
-use super::super::{DistanceBoard, Owner};
+use super::super::board::distance_board::DistanceSearch;
+use super::super::board::territory_board::{Control, TerritoryBoard};
+use super::super::board::region_board::{IslandStatus, RegionBoard};
+use super::super::board::chokepoint_board::ChokepointBoard;
+use super::super::board::Owner;
 use super::super::action::Action;
+use super::super::assignment::AssignmentMovePolicy;
+use super::super::evaluation::MatterRobotScore;
+use super::composite::{BuildPolicy, CompositeAgent, MatterBudget, MovePolicy, Overlays, SpawnPolicy};
 use super::Agent;
 use super::super::board::Board;
 
@@ -13,108 +21,273 @@ use super::super::board::Board;
 ///
 /// The philosophy is as follows:
 /// Assuming the board stays a connected graph
-/// (Which is a dubious assumption)
+/// (Which is a dubious assumption, so islands that are already settled don't get any more robots)
 /// The person with the most scrap invested in Robots, wins
 ///
 /// Don't mine too much than necessary to keep the board connected
-
 pub struct SimpleEconomyAgent {
     pub min_scrap_lead: i32,
     pub recycler_min_score: i32,
-    pub expected_mining_discount: f32,
+    pub economy: MatterRobotScore, // the score of both players that `min_scrap_lead` compares
     pub distance_move_weighting: u32, // how important it is to move closer vs. spreading out
     pub distance_mine_weighting: i32, // how much recycler distance weighs vs. yield
     pub recycler_robot_adjacency_weight: u32,
+    pub recycler_territory_weight: i32, // how bad it is to turn fields we'd get first into grass
+    pub spawn_contested_weight: u32, // how much being next to fields we don't get first draws spawns
+    pub recycler_placement: RecyclerPlacement,
+    pub chokepoint_min_territory: i32,
     pub movement_own_score: u32,
     pub movement_neutral_score: u32,
     pub movement_opponent_score: u32,
+    /// Moves all robots at once with an optimal assignment instead of `GreedyMovePolicy`
+    pub move_policy: Option<AssignmentMovePolicy>,
+    /// Draws the board, the territory and the recycler scores to stderr every turn
+    pub debug_render: Option<RenderMode>,
+    /// Reused for the distance boards of every turn
+    pub distance_search: DistanceSearch,
+}
+
+/// How the agent picks the fields for recyclers
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RecyclerPlacement {
+    /// Where they mine the most, when we're behind on matter
+    Yield,
+    /// Where they cut the opponent off from the most territory, whenever there is such a field.
+    /// Recyclers for yield are still built with the matter that's left when we're behind.
+    Chokepoint,
 }
 
+impl Default for SimpleEconomyAgent {
+    fn default() -> Self {
+        SimpleEconomyAgent {
+            min_scrap_lead: 5,
+            recycler_min_score: 30,
+            economy: MatterRobotScore::default(),
+            distance_move_weighting: 3,
+            distance_mine_weighting: 2,
+            recycler_robot_adjacency_weight: 8,
+            recycler_territory_weight: 6,
+            spawn_contested_weight: 2,
+            recycler_placement: RecyclerPlacement::Yield,
+            chokepoint_min_territory: 6,
+            movement_own_score: 5,
+            movement_neutral_score: 0,
+            movement_opponent_score: 3,
+            move_policy: None,
+            debug_render: None,
+            distance_search: DistanceSearch::default(),
+        }
+    }
+}
+
+/// Plays the turn of the `CompositeAgent` made from its policies
 impl Agent for SimpleEconomyAgent {
     fn generate_actions(&mut self, board: Board) -> Vec<Action> {
-        let expected_mining = YieldBoard::without_recycling(&board); // yields, ignoring all recyclers
-        let prospective_mining = YieldBoard::with_recycling(&board); // yields, accounting for current recyclers
-
-        let my_robot_count = board.robot_count(Owner::Me);
-        let opponent_robot_count = board.robot_count(Owner::Opponent);
+        if let Some(mode) = self.debug_render {
+            eprint!("{}", Renderer::new(mode).render_board(&board));
+            eprint!("territory, {}", Renderer::new(mode).render_overlay(&board, &TerritoryBoard::new(&board).control));
+        }
 
-        let my_yield = expected_yield(&board, &expected_mining, Owner::Me);
-        let my_matter_robot_score = board.my_matter + 10 * my_robot_count + (my_yield as f32 * self.expected_mining_discount) as u32;
-        let opponent_yield = expected_yield(&board, &expected_mining, Owner::Opponent);
-        let opponent_matter_robot_score = board.opponent_matter + 10 * opponent_robot_count + (opponent_yield as f32 * self.expected_mining_discount) as u32;
+        let mut composite = CompositeAgent::from(&*self);
+        composite.distance_search = std::mem::take(&mut self.distance_search);
+        let result = composite.generate_actions(board);
+        self.distance_search = composite.distance_search;
 
-        let opponent_distance_board = DistanceBoard::from_owner(&board, Owner::Opponent);
+        result
+    }
+}
 
-        let mut result: Vec<Action> = Vec::new();
-        result.extend(self.move_robots(&board, &opponent_distance_board));
+impl SimpleEconomyAgent {
 
-        let mut scrap_to_spend = board.my_matter;
-        if (my_matter_robot_score as i32) < (opponent_matter_robot_score as i32 + self.min_scrap_lead) {
-            let build_commands = self.build_recyclers(&board, &prospective_mining, &opponent_distance_board,  scrap_to_spend / 10);
-            scrap_to_spend -= build_commands.len() as u32 * 10;
-            result.extend(build_commands);
+    pub fn greedy_move_policy(&self) -> GreedyMovePolicy {
+        GreedyMovePolicy {
+            own_score: self.movement_own_score,
         }
-        result.extend(self.spawn_robots(&board, &opponent_distance_board, scrap_to_spend / 10));
+    }
 
-        result
+    pub fn yield_build_policy(&self) -> YieldBuildPolicy {
+        YieldBuildPolicy {
+            economy: self.economy,
+            min_scrap_lead: self.min_scrap_lead,
+            min_score: self.recycler_min_score,
+            distance_weighting: self.distance_mine_weighting,
+            robot_adjacency_weight: self.recycler_robot_adjacency_weight,
+            territory_weight: self.recycler_territory_weight,
+            debug_render: self.debug_render,
+        }
     }
-}
 
-impl SimpleEconomyAgent {
+    pub fn chokepoint_build_policy(&self) -> ChokepointBuildPolicy {
+        ChokepointBuildPolicy {
+            min_territory: self.chokepoint_min_territory,
+            debug_render: self.debug_render,
+        }
+    }
 
-    fn build_recyclers(&mut self, board: &Board, yield_board: &YieldBoard, opponent_distance_board: &DistanceBoard, amount: u32) -> Vec<Action> {
-        let mut result: Vec<Action> = Vec::new();
-        let recycler_range_board = RecyclerRangeBoard::from_board(board);
+    pub fn front_spawn_policy(&self) -> FrontSpawnPolicy {
+        FrontSpawnPolicy {
+            contested_weight: self.spawn_contested_weight,
+        }
+    }
+}
 
-        let mut field_score =  board.fields
-            .iter()
-            .filter(|x| x.owner == Owner::Me)
-            .filter(|x| x.is_traversible())
-            .filter(|x| x.num_units == 0)
-            .filter(|x| !opponent_distance_board.get_field(x.x, x.y).unwrap().is_unreachable())
-            .map(|x| (x, yield_board.prospective_scrap[(x.x + x.y * board.width) as usize], opponent_distance_board.distances[(x.x + x.y * board.width) as usize]))
-            .map(|(a, y, dist)| {
-                let enemy_robot_score = board.adjacent_robot_count(a.x, a.y, Owner::Opponent) * self.recycler_robot_adjacency_weight;
-                let score = enemy_robot_score as i32 + y as i32 - self.distance_mine_weighting * dist.distance_or_panic() as i32;
-                (a, y, score)
-            })
-            .collect::<Vec<_>>();
+/// Builds recyclers where they mine the most, when we're behind on matter
+#[derive(Copy, Clone, Debug)]
+pub struct YieldBuildPolicy {
+    pub economy: MatterRobotScore,
+    /// The lead in `economy` score we want over the opponent
+    pub min_scrap_lead: i32,
+    pub min_score: i32,
+    pub distance_weighting: i32, // how much recycler distance weighs vs. yield
+    pub robot_adjacency_weight: u32,
+    pub territory_weight: i32, // how bad it is to turn fields we'd get first into grass
+    pub debug_render: Option<RenderMode>,
+}
 
-        field_score.sort_by(|(_, _, a), (_, _, b)| (*a).cmp(b).reverse());
+impl YieldBuildPolicy {
 
-        // TODO: check if recycler is in range of another => then skip
-        for (field, _, score) in field_score.into_iter().take((board.my_matter / 10) as usize) {
-            if score < self.recycler_min_score {
-                break;
+    /// How good a recycler would be on each field, `None` where one can't or shouldn't be built
+    pub fn recycler_scores(&self, board: &Board, yield_board: &YieldBoard, territory_board: &TerritoryBoard) -> Grid<Option<i32>> {
+        Grid::from_board(board, |x| {
+            if x.owner != Owner::Me || !x.is_traversible() || x.num_units > 0 {
+                return None;
             }
 
-            if !recycler_range_board.get_field(field.x, field.y).unwrap() {
-                eprintln!("x {} y {} prospective score {}", field.x, field.y, score);
-                result.push(Action::Build(field.x, field.y))
+            let dist = territory_board.theirs.distances[x.coord()];
+            if dist.is_unreachable() {
+                return None;
             }
+
+            // fields with no more scrap than this one turn to grass before the recycler runs out
+            let territory_balance = x.coord()
+                .in_range(board.width, board.height)
+                .filter(|c| board.fields[c.index(board.width)].scrap_amount <= x.scrap_amount)
+                .map(|c| match territory_board.control[c] {
+                    Control::Mine(_) => 1,
+                    Control::Theirs(_) => -1,
+                    _ => 0,
+                })
+                .sum::<i32>();
+
+            let y = yield_board.prospective_scrap[x.coord()];
+            let enemy_robot_score = board.adjacent_robot_count(x.x, x.y, Owner::Opponent) * self.robot_adjacency_weight;
+            Some(enemy_robot_score as i32 + y as i32
+                - self.distance_weighting * dist.distance_or_panic() as i32
+                - self.territory_weight * territory_balance)
+        })
+    }
+
+}
+
+impl BuildPolicy for YieldBuildPolicy {
+    fn builds(&mut self, board: &Board, overlays: &Overlays, budget: &MatterBudget) -> Vec<Action> {
+        // whether we're behind is decided on the board before this turn's builds,
+        // a chokepoint recycler built just now would count as income already
+        let my_matter_robot_score = self.economy.score(&overlays.board, &overlays.expected_mining, Owner::Me);
+        let opponent_matter_robot_score = self.economy.score(&overlays.board, &overlays.expected_mining, Owner::Opponent);
+        if (my_matter_robot_score as i32) >= (opponent_matter_robot_score as i32 + self.min_scrap_lead) {
+            return Vec::new();
         }
 
-        result
+        let scores = self.recycler_scores(board, &overlays.prospective_mining, &overlays.territory_board);
+        build_recyclers(board, &scores, self.min_score, RecyclerPlacement::Yield, budget.recyclers(), self.debug_render)
     }
+}
 
-    fn spawn_robots(&mut self, board: &Board, opponent_distance_board: &DistanceBoard, amount: u32) -> Vec<Action> {
-        let mut result: Vec<Action> = Vec::new();
+/// Builds recyclers where they cut the opponent off from the most territory
+#[derive(Copy, Clone, Debug)]
+pub struct ChokepointBuildPolicy {
+    pub min_territory: i32,
+    pub debug_render: Option<RenderMode>,
+}
 
-        let neutral_distance_board = DistanceBoard::from_owner(board, Owner::Neutral);
-        let mut distance_board_to_use =
-            if zip(opponent_distance_board.distances.iter(), board.fields.iter())
-                .all(|(dist, f)| dist.is_unreachable() || f.owner != Owner::Me)
-            {
-                &neutral_distance_board
-            } else {
-                opponent_distance_board
-            };
+impl ChokepointBuildPolicy {
+
+    /// The territory a recycler on each field would cut off from the opponent,
+    /// less the fields we get first that it turns into grass
+    pub fn chokepoint_scores(&self, board: &Board, territory_board: &TerritoryBoard) -> Grid<Option<i32>> {
+        let chokepoint_board = ChokepointBoard::new(board);
+
+        Grid::from_board(board, |x| {
+            let sealed = chokepoint_board.sealed_territory[x.coord()]?;
+            let lost = x.coord()
+                .in_range(board.width, board.height)
+                .filter(|c| board.fields[c.index(board.width)].scrap_amount <= x.scrap_amount)
+                .filter(|c| matches!(territory_board.control[*c], Control::Mine(_)))
+                .count() as i32;
+            Some(sealed - lost)
+        })
+    }
+}
+
+impl BuildPolicy for ChokepointBuildPolicy {
+    fn builds(&mut self, board: &Board, overlays: &Overlays, budget: &MatterBudget) -> Vec<Action> {
+        let scores = self.chokepoint_scores(board, &overlays.territory_board);
+        build_recyclers(board, &scores, self.min_territory, RecyclerPlacement::Chokepoint, budget.recyclers(), self.debug_render)
+    }
+}
+
+/// Up to `amount` recyclers on the best scored fields, none below `min_score`
+fn build_recyclers(board: &Board, scores: &Grid<Option<i32>>, min_score: i32, placement: RecyclerPlacement, amount: u32, debug_render: Option<RenderMode>) -> Vec<Action> {
+    let mut result: Vec<Action> = Vec::new();
+    let recycler_range_board = RecyclerRangeBoard::from_board(board);
+    if let Some(mode) = debug_render {
+        eprint!("{:?} recycler scores, {}", placement, Renderer::new(mode).render_overlay(board, scores));
+    }
+
+    let mut field_score = scores
+        .enumerate()
+        .filter_map(|(coord, score)| score.map(|x| (coord, x)))
+        .collect::<Vec<_>>();
+
+    field_score.sort_by(|(_, a), (_, b)| (*a).cmp(b).reverse());
+
+    // TODO: check if recycler is in range of another => then skip
+    for (coord, score) in field_score.into_iter().take(amount as usize) {
+        if score < min_score {
+            break;
+        }
+
+        // a recycler that cuts the map off is worth building even where the scrap is already mined
+        if !recycler_range_board.in_range[coord] || placement == RecyclerPlacement::Chokepoint {
+            result.push(Action::Build(coord.x, coord.y))
+        }
+    }
+
+    result
+}
+
+/// Spawns robots next to the fields we don't own, near the front and the stacks that are there already
+#[derive(Copy, Clone, Debug)]
+pub struct FrontSpawnPolicy {
+    pub contested_weight: u32, // how much being next to fields we don't get first draws spawns
+}
+
+impl SpawnPolicy for FrontSpawnPolicy {
+    fn spawns(&mut self, board: &Board, overlays: &Overlays, budget: &MatterBudget) -> Vec<Action> {
+        let territory_board = &overlays.territory_board;
+        let opponent_distance_board = &overlays.opponent_distance_board;
+        let neutral_distance_board = &overlays.neutral_distance_board;
+        let mine_duration_board = &overlays.mine_duration_board;
+        let amount = budget.robots();
+        let mut result: Vec<Action> = Vec::new();
+        let region_board = RegionBoard::new(board);
 
         let field_dist = board.fields
             .iter()
             .filter(|x| x.owner == Owner::Me)
             .filter(|x| x.is_traversible())
-            .map(|x| (x, distance_board_to_use.get_field(x.x, x.y).unwrap()))
+            .filter(|x| mine_duration_board.is_safe_after(x.x, x.y, 1)) // don't spawn onto doomed fields
+            .filter_map(|x| region_board.island_at(x.x, x.y).map(|island| (x, island)))
+            .filter(|(_, island)| !island.is_settled())
+            .map(|(x, island)| {
+                // on an island without the opponent, only the neutral fields are left to take
+                let distance_board_to_use = match island.status() {
+                    IslandStatus::Contested => opponent_distance_board,
+                    _ => neutral_distance_board,
+                };
+                (x, distance_board_to_use.get_field(x.x, x.y).unwrap())
+            })
             .filter(|(_, dist)| !dist.is_unreachable())
             .filter(|(f, _)| { // consider only fields that are adjacent to unowned tiles
                 let adj = board.get_adjacent_fields(f.x, f.y);
@@ -126,14 +299,18 @@ impl SimpleEconomyAgent {
 
         let mut field_score = field_dist
             .map(|(f, dist)| {
-                let score = 2 * f.num_units + dist.distance_or_panic();
+                // fields away from the front are only used when there's nothing better
+                let at_front = Coord::new(f.x, f.y)
+                    .neighbours(board.width, board.height)
+                    .any(|c| matches!(territory_board.control[c], Control::Contested | Control::Theirs(_)));
+                let score = 2 * f.num_units + dist.distance_or_panic() + if at_front { 0 } else { self.contested_weight };
                 (f, score)
             })
             .collect::<Vec<_>>();
 
-        field_score.sort_by(|(_, a), (_, b)| a.cmp(b));
+        field_score.sort_by_key(|(_, a)| *a);
 
-        if field_score.len() > 0 {
+        if !field_score.is_empty() {
             let mut amount_placed = vec![0_u32; field_score.len()];
             let mut aspiration_score = field_score[0].1;
             let mut amount_to_go = amount;
@@ -144,7 +321,7 @@ impl SimpleEconomyAgent {
                 .cycle();
 
             while amount_to_go > 0 {
-                let (index, (f, score)) = field_score_cycled.next().unwrap();
+                let (index, (_, score)) = field_score_cycled.next().unwrap();
                 if index == 0 {
                     aspiration_score += 1;
                 }
@@ -156,7 +333,7 @@ impl SimpleEconomyAgent {
             }
 
             result.extend(
-                zip(amount_placed.into_iter(), field_score)
+                zip(amount_placed, field_score)
                     .map(|(a, (f, _))| Action::Spawn(a, f.x, f.y))
             );
         }
@@ -187,20 +364,29 @@ impl SimpleEconomyAgent {
         //
         // result
     }
+}
+
+/// Steps every stack towards the opponent, spreading its units over the closest fields,
+/// or over the shortest routes to neutral fields when the opponent can't be reached
+#[derive(Copy, Clone, Debug)]
+pub struct GreedyMovePolicy {
+    pub own_score: u32, // how much a field one of our robots already moves onto puts off the next ones
+}
 
-    fn move_robots(&self, board: &Board, opponent_distance_board: &DistanceBoard) -> Vec<Action> {
+impl MovePolicy for GreedyMovePolicy {
+    fn moves(&mut self, board: &Board, overlays: &Overlays, _search: &mut DistanceSearch) -> Vec<Action> {
+        let opponent_distance_board = &overlays.opponent_distance_board;
+        let neutral_distance_board = &overlays.neutral_distance_board;
+        let mine_duration_board = &overlays.mine_duration_board;
         let mut result = Vec::new();
-        // keeps track of how many robots will be on a given field at the end of a turn
-        let arrival_count_board: Vec<u32> = vec![0; board.fields.len()];
 
         let mut my_robot_coords = zip(board.fields.iter(), opponent_distance_board.distances.iter())
             .filter(|(x, _)| x.owner == Owner::Me && x.num_units > 0)
             .map(|(a, b)| (a.x, a.y, a.num_units, *b))
             .collect::<Vec<_>>();
 
-        my_robot_coords.sort_by(|(_, _, _, a), (_, _, _, b)| a.cmp(b));
+        my_robot_coords.sort_by_key(|(_, _, _, a)| *a);
 
-        let neutral_distance_board = DistanceBoard::from_owner(board, Owner::Neutral);
         let mut owner_score = board.fields
             .iter()
             .map(|f| match f.owner {
@@ -212,14 +398,10 @@ impl SimpleEconomyAgent {
         let mut robot_arrival_board = vec![0_u32; board.fields.len()];
 
         for (x, y, num_units, _) in my_robot_coords.into_iter() {
-            let adjacent_locations = adjacent_in_range(x, y, board.width, board.height);
-            let adjacent_locations = adjacent_locations
-                .into_iter()
-                .flatten()
-                .filter(|(x, y)| {
-                    let field = board.get_field(*x, *y).unwrap();
-                    !(field.scrap_amount <= 1 && field.in_recycler_range)
-                });
+            let adjacent_locations = Coord::new(x, y)
+                .neighbours(board.width, board.height)
+                .map(<(u32, u32)>::from)
+                .filter(|(x, y)| mine_duration_board.is_safe_after(*x, *y, 1));
 
             // (x, y, dist)
             let adjacent_distances = adjacent_locations
@@ -247,7 +429,7 @@ impl SimpleEconomyAgent {
             //     })
             //     .collect::<Vec<_>>();
 
-            if adjacent_distances.len() > 0 {
+            if !adjacent_distances.is_empty() {
                 // enemy reachable
                 let mut current_aspiration_score = adjacent_distances
                     .iter()
@@ -275,14 +457,14 @@ impl SimpleEconomyAgent {
 
                     if robot_arrival_board[field_index] + dist + owner_score[field_index] < current_aspiration_score {
                         robot_arrival_board[field_index] += 1;
-                        owner_score[field_index] = self.movement_own_score;
+                        owner_score[field_index] = self.own_score;
                         move_towards[index] += 1;
                         num_units_left -= 1;
                     }
                 }
 
                 result.extend(
-                    zip(adjacent_distances.iter(), move_towards.into_iter())
+                    zip(adjacent_distances.iter(), move_towards)
                         .filter(|(_, amount)| *amount > 0)
                         .map(|((to_x, to_y, _), amount)| Action::Move {
                             amount,
@@ -291,26 +473,35 @@ impl SimpleEconomyAgent {
                         })
                 )
             } else {
-                // enemy unreachable
-                let direction_num = neutral_distance_board
-                    .towards(x, y, Ordering::Less)
-                    .iter()
-                    .position(|x| *x);
-
-                let to_field = match direction_num {
-                    Some(0) => (x, y - 1),
-                    Some(1) => (x + 1, y),
-                    Some(2) => (x, y + 1),
-                    Some(3) => (x - 1, y),
-                    None => (x, y),
-                    _ => panic!("robot_coords should hold only 4 items"),
-                };
-
-                result.push(Action::Move {
-                    amount: num_units,
-                    from: (x, y),
-                    to: to_field,
-                })
+                // enemy unreachable: spread over all the shortest routes to neutral fields,
+                // the steps with the most routes through them first
+                let here = Coord::new(x, y);
+                let route_counts = neutral_distance_board.shortest_path_counts(here);
+                let mut next_steps = neutral_distance_board
+                    .next_steps(here)
+                    .into_iter()
+                    .filter(|c| mine_duration_board.is_safe_after(c.x, c.y, 1))
+                    .collect::<Vec<_>>();
+                next_steps.sort_by_key(|c| Reverse(route_counts[*c]));
+
+                if next_steps.is_empty() {
+                    result.push(Action::Move {
+                        amount: num_units,
+                        from: (x, y),
+                        to: (x, y),
+                    })
+                }
+                let step_count = next_steps.len() as u32;
+                for (i, to) in next_steps.into_iter().enumerate() {
+                    let amount = num_units / step_count + u32::from((i as u32) < num_units % step_count);
+                    if amount > 0 {
+                        result.push(Action::Move {
+                            amount,
+                            from: (x, y),
+                            to: (to.x, to.y),
+                        })
+                    }
+                }
             }
 
 
@@ -340,15 +531,28 @@ impl SimpleEconomyAgent {
     }
 }
 
-fn expected_yield(board: &Board, yield_board: &YieldBoard, owner: Owner) -> u32 {
-    let fields = board.fields.iter();
-    let yields = yield_board.prospective_scrap.iter();
-
-    let result = zip(fields, yields)
-        .filter(|(f, _)| f.has_recycler)
-        .filter(|(f, _)| f.owner == owner)
-        .map(|(_, y)| *y)
-        .sum::<u32>();
-
-    result
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chokepoint_and_yield_recyclers_go_on_different_fields() {
+        let board = Board::from_ascii("
+            5m1 5m  . .  .  5o  5o2
+            5m  9m  9m 9 9  5o  5o2
+            5m  5m  . .  .  5o2 5o2
+        ").unwrap();
+        let board = Board { my_matter: 20, opponent_matter: 0, ..board };
+        let mut agent = SimpleEconomyAgent {
+            recycler_placement: RecyclerPlacement::Chokepoint,
+            chokepoint_min_territory: 1,
+            recycler_min_score: 0,
+            ..Default::default()
+        };
+
+        // (2, 1) is both the chokepoint and the best field for yield
+        let actions = agent.generate_actions(board);
+        assert_eq!(actions.iter().filter(|x| matches!(x, Action::Build(..))).collect::<Vec<_>>(), vec![&Action::Build(2, 1)]);
+        assert!(!actions.iter().any(|x| matches!(x, Action::Spawn(_, 2, 1))));
+    }
 }
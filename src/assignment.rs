@@ -0,0 +1,301 @@
+//! Sends robots where they are worth the most, all stacks at once
+//!
+//! Robot stacks and target fields form a min-cost flow: every unit flows from its stack to a target,
+//! paying for the distance and earning the target's value, until a target's demand is met.
+//! Units are only sent where that pays off, so a target too far away for its value gets nobody.
+
+use std::collections::VecDeque;
+use super::action::Action;
+use super::agent::composite::{MovePolicy, Overlays};
+use super::board::distance_board::{DistanceBoard, DistanceSearch};
+use super::board::{Board, Coord, Grid, Owner};
+use super::flow::{FlowNetwork, INFINITE};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Stack {
+    pub coord: Coord,
+    pub units: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Target {
+    pub coord: Coord,
+    /// How many units the target can use
+    pub demand: u32,
+    /// What every unit that gets there is worth
+    pub value: i32,
+}
+
+/// Part of a stack that is sent to a target
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Dispatch {
+    pub stack: usize,
+    pub target: usize,
+    pub units: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AssignmentProblem {
+    pub stacks: Vec<Stack>,
+    pub targets: Vec<Target>,
+    /// The cost of sending a unit, by `[stack][target]`; `None` where the stack can't get to the target
+    pub costs: Vec<Vec<Option<i32>>>,
+}
+
+impl AssignmentProblem {
+
+    /// The dispatches with the highest total of value minus cost
+    pub fn solve(&self) -> Vec<Dispatch> {
+        let source = self.stacks.len() + self.targets.len();
+        let sink = source + 1;
+        let target_node = |t: usize| self.stacks.len() + t;
+
+        let mut network = FlowNetwork::new(sink + 1);
+        for (s, stack) in self.stacks.iter().enumerate() {
+            network.add_edge(source, s, stack.units as i64);
+        }
+        for (t, target) in self.targets.iter().enumerate() {
+            network.add_edge(target_node(t), sink, target.demand as i64);
+        }
+        let mut edges = Vec::new();
+        for (s, costs) in self.costs.iter().enumerate() {
+            for (t, cost) in costs.iter().enumerate() {
+                if let Some(cost) = cost {
+                    let profit = self.targets[t].value as i64 - *cost as i64;
+                    if profit > 0 {
+                        edges.push((s, t, network.add_edge_with_cost(s, target_node(t), INFINITE, -profit)));
+                    }
+                }
+            }
+        }
+
+        min_cost_flow(&mut network, source, sink);
+
+        edges
+            .into_iter()
+            .filter(|(_, _, edge)| network.flow(*edge) > 0)
+            .map(|(stack, target, edge)| Dispatch { stack, target, units: network.flow(edge) as u32 })
+            .collect()
+    }
+}
+
+/// Moves all robots at once by solving an `AssignmentProblem`, instead of stack by stack
+///
+/// Targets are the fields left to take, and our fields next to opponent robots.
+/// Units that aren't worth sending anywhere follow the distance board they are given.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AssignmentMovePolicy {
+    pub neutral_value: i32,
+    pub opponent_value: i32,
+    /// The value of a unit on one of our fields the opponent can step onto
+    pub defense_value: i32,
+    /// The cost of every turn a unit takes to get to its target
+    pub distance_cost: i32,
+}
+
+impl Default for AssignmentMovePolicy {
+    fn default() -> Self {
+        Self {
+            neutral_value: 10,
+            opponent_value: 14,
+            defense_value: 12,
+            distance_cost: 3,
+        }
+    }
+}
+
+impl AssignmentMovePolicy {
+
+    pub fn problem(&self, board: &Board, distances: &[DistanceBoard]) -> AssignmentProblem {
+        let stacks = board.fields
+            .iter()
+            .filter(|x| x.owner == Owner::Me && x.num_units > 0)
+            .map(|x| Stack { coord: x.coord(), units: x.num_units })
+            .collect::<Vec<_>>();
+
+        let targets = board.fields
+            .iter()
+            .filter(|x| x.is_traversible())
+            .filter_map(|x| match x.owner {
+                Owner::Neutral => Some(Target { coord: x.coord(), demand: 1, value: self.neutral_value }),
+                Owner::Opponent => Some(Target { coord: x.coord(), demand: x.num_units + 1, value: self.opponent_value }),
+                Owner::Me => {
+                    let threat = board.adjacent_robot_count(x.x, x.y, Owner::Opponent);
+                    (threat > 0).then_some(Target { coord: x.coord(), demand: threat, value: self.defense_value })
+                },
+            })
+            .collect::<Vec<_>>();
+
+        let costs = distances
+            .iter()
+            .map(|distance_board| targets
+                .iter()
+                .map(|target| match distance_board.distances[target.coord] {
+                    d if d.is_unreachable() => None,
+                    d => Some(d.distance_or_panic() as i32 * self.distance_cost),
+                })
+                .collect())
+            .collect();
+
+        AssignmentProblem {
+            stacks,
+            targets,
+            costs,
+        }
+    }
+}
+
+impl MovePolicy for AssignmentMovePolicy {
+    /// The moves for all our robots; units without a target take a step towards the opponent
+    fn moves(&mut self, board: &Board, overlays: &Overlays, search: &mut DistanceSearch) -> Vec<Action> {
+        let mine_duration_board = &overlays.mine_duration_board;
+        let fallback = &overlays.opponent_distance_board;
+        let distances = board.fields
+            .iter()
+            .filter(|x| x.owner == Owner::Me && x.num_units > 0)
+            .map(|x| search.from_coord_with_grass_forecast(board, x.coord(), 0, mine_duration_board))
+            .collect::<Vec<_>>();
+        let problem = self.problem(board, &distances);
+        // the distances from every field a stack can step onto, to tell which steps lead to a target;
+        // the robots get there next turn, so these count from then like `distances`
+        let steps = problem.stacks
+            .iter()
+            .map(|stack| stack.coord
+                .neighbours(board.width, board.height)
+                .filter(|c| mine_duration_board.is_safe_after(c.x, c.y, 1))
+                .map(|c| (c, search.from_coord_with_grass_forecast(board, c, 1, mine_duration_board)))
+                .collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let dispatches = problem.solve();
+
+        let mut result: Vec<Action> = Vec::new();
+        let mut units_left = problem.stacks.iter().map(|x| x.units).collect::<Vec<_>>();
+        let mut arrivals = Grid::filled(board.width, board.height, 0_u32);
+        for dispatch in dispatches {
+            let from = problem.stacks[dispatch.stack].coord;
+            let target = problem.targets[dispatch.target].coord;
+            units_left[dispatch.stack] -= dispatch.units;
+            if from == target {
+                continue;
+            }
+
+            // every neighbour on a shortest path to the target, which reaches it just as early;
+            // spread the units over them, onto fields we don't own yet first
+            let distance = distances[dispatch.stack].distances[target];
+            let shortest_steps = steps[dispatch.stack]
+                .iter()
+                .filter(|(_, via)| via.distances[target] <= distance)
+                .map(|(c, _)| *c)
+                .collect::<Vec<_>>();
+
+            for _ in 0..dispatch.units {
+                let Some(&to) = shortest_steps
+                    .iter()
+                    .min_by_key(|c| (arrivals[**c], board.fields[c.index(board.width)].owner == Owner::Me))
+                else {
+                    break;
+                };
+                arrivals[to] += 1;
+                match result.iter_mut().find(|x| matches!(x, Action::Move { from: f, to: t, .. } if *f == from.into() && *t == to.into())) {
+                    Some(Action::Move { amount, .. }) => *amount += 1,
+                    _ => result.push(Action::Move { amount: 1, from: from.into(), to: to.into() }),
+                }
+            }
+        }
+
+        for (stack, units) in problem.stacks.iter().zip(units_left).filter(|(_, units)| *units > 0) {
+            let to = fallback
+                .next_steps(stack.coord)
+                .into_iter()
+                .find(|c| mine_duration_board.is_safe_after(c.x, c.y, 1));
+            if let Some(to) = to {
+                result.push(Action::Move { amount: units, from: stack.coord.into(), to: to.into() });
+            }
+        }
+
+        for distance_board in distances.into_iter().chain(steps.into_iter().flatten().map(|(_, x)| x)) {
+            search.recycle(distance_board);
+        }
+        result
+    }
+}
+
+/// Successive shortest paths, found with Bellman-Ford as costs can be negative,
+/// for as long as they lower the total cost
+fn min_cost_flow(network: &mut FlowNetwork, source: usize, sink: usize) {
+    network.augment(source, sink, |network| {
+        let mut cost = vec![INFINITE; network.node_count()];
+        let mut parent_edge = vec![usize::MAX; network.node_count()];
+        let mut queued = vec![false; network.node_count()];
+        let mut frontier = VecDeque::from([source]);
+        cost[source] = 0;
+
+        while let Some(node) = frontier.pop_front() {
+            queued[node] = false;
+            for &edge in network.edges_from(node) {
+                let to = network.to(edge);
+                let next_cost = cost[node] + network.cost(edge);
+                if network.residual(edge) > 0 && next_cost < cost[to] {
+                    cost[to] = next_cost;
+                    parent_edge[to] = edge;
+                    if !queued[to] {
+                        queued[to] = true;
+                        frontier.push_back(to);
+                    }
+                }
+            }
+        }
+
+        (cost[sink] < 0).then_some(parent_edge)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_stacks_can_take_over_targets() {
+        // greedily, stack 0 takes the target it is closest to, leaving stack 1 nothing
+        let problem = AssignmentProblem {
+            stacks: vec![Stack { coord: Coord::new(0, 0), units: 1 }, Stack { coord: Coord::new(1, 0), units: 1 }],
+            targets: vec![Target { coord: Coord::new(2, 0), demand: 1, value: 10 }, Target { coord: Coord::new(3, 0), demand: 1, value: 10 }],
+            costs: vec![vec![Some(1), Some(2)], vec![Some(1), None]],
+        };
+
+        let mut dispatches = problem.solve();
+        dispatches.sort_by_key(|x| x.stack);
+        assert_eq!(dispatches, vec![
+            Dispatch { stack: 0, target: 1, units: 1 },
+            Dispatch { stack: 1, target: 0, units: 1 },
+        ]);
+    }
+
+    #[test]
+    fn units_only_go_where_it_pays_off() {
+        let problem = AssignmentProblem {
+            stacks: vec![Stack { coord: Coord::new(0, 0), units: 5 }],
+            targets: vec![Target { coord: Coord::new(1, 0), demand: 3, value: 4 }, Target { coord: Coord::new(5, 0), demand: 3, value: 4 }],
+            costs: vec![vec![Some(2), Some(4)]],
+        };
+
+        assert_eq!(problem.solve(), vec![Dispatch { stack: 0, target: 0, units: 3 }]);
+    }
+
+    #[test]
+    fn moves_send_robots_towards_their_targets() {
+        let board = Board::from_ascii("
+            5m2 5m 5 5o1
+            5   .  . 5o
+        ").unwrap();
+        let mut search = DistanceSearch::default();
+        let overlays = Overlays::new(&board, &mut search);
+        let moves = AssignmentMovePolicy::default().moves(&board, &overlays, &mut search);
+
+        // one unit takes the neutral field below, the other heads for the opponent
+        assert_eq!(moves, vec![
+            Action::Move { amount: 1, from: (0, 0), to: (1, 0) },
+            Action::Move { amount: 1, from: (0, 0), to: (0, 1) },
+        ]);
+    }
+}
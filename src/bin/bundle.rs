@@ -0,0 +1,82 @@
+//! Writes the bot as the single source file CodinGame takes for a submission
+//!
+//! Usage: `bundle [output file]`, which prints to stdout without one.
+//! For example `cargo run --bin bundle submission.rs`, then paste `submission.rs` into the IDE.
+//!
+//! Every `mod name;` of the library is replaced by `mod name { ... }` with the contents of its file,
+//! and the modules are put next to `main.rs`, which uses them through `crate::` instead of the library.
+//! Tests are kept, they are compiled out like in the library.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const LIBRARY: &str = "codingame_keep_off_the_grass";
+
+/// The module declared by a line like `pub mod name;`, with the visibility in front of it
+fn module_declaration(line: &str) -> Option<(&str, &str)> {
+    let declaration = line.trim().strip_suffix(';')?;
+    let (visibility, name) = declaration.rsplit_once("mod ")?;
+    let visible = visibility.trim().is_empty() || visibility.trim().starts_with("pub");
+    if !visible || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some((visibility, name))
+}
+
+/// The contents of `file` with its module declarations inlined, looking for the modules in `directory`
+fn inline_modules(file: &Path, directory: &Path) -> io::Result<String> {
+    let source = fs::read_to_string(file)?;
+    let mut result = String::new();
+
+    for line in source.lines() {
+        let (visibility, name) = match module_declaration(line) {
+            Some(x) => x,
+            None => {
+                result.push_str(line);
+                result.push('\n');
+                continue;
+            },
+        };
+
+        let (module_file, module_directory) = match directory.join(format!("{}.rs", name)) {
+            x if x.exists() => (x, directory.join(name)),
+            _ => (directory.join(name).join("mod.rs"), directory.join(name)),
+        };
+        result.push_str(&format!("{}mod {} {{\n", visibility, name));
+        result.push_str(&inline_modules(&module_file, &module_directory)?);
+        result.push_str("}\n");
+    }
+
+    Ok(result)
+}
+
+fn bundle(source: &Path) -> io::Result<String> {
+    let main = fs::read_to_string(source.join("main.rs"))?.replace(&format!("{}::", LIBRARY), "crate::");
+    let library = inline_modules(&source.join("lib.rs"), source)?;
+
+    // library items the bot doesn't use would otherwise warn in a binary
+    Ok(format!("#![allow(dead_code)]\n\n{}\n{}", main, library))
+}
+
+fn main() {
+    let source = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src");
+    let bundled = bundle(&source).unwrap_or_else(|e| {
+        eprintln!("could not read the sources: {}", e);
+        process::exit(1);
+    });
+
+    let written = match env::args().nth(1) {
+        Some(path) => fs::write(path, bundled),
+        None => {
+            print!("{}", bundled);
+            Ok(())
+        },
+    };
+    if let Err(e) = written {
+        eprintln!("could not write the bundle: {}", e);
+        process::exit(1);
+    }
+}
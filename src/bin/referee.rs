@@ -0,0 +1,178 @@
+//! Plays two bots against each other, using the same protocol as the CodinGame referee
+//!
+//! Usage: `referee [--verbose] [--seed <seed>] <command of player 1> <command of player 2>`
+//!
+//! Both commands are run through `sh -c`, so they can contain arguments.
+//! Without a seed a random map is played; the seed is printed so the game can be replayed.
+//! Player 1 is `Owner::Me` on the referee's board, player 2 is `Owner::Opponent`.
+//! A bot that doesn't answer within CodinGame's time limits loses, like one that quits.
+
+use std::env;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{self, Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use codingame_keep_off_the_grass::action::Action;
+use codingame_keep_off_the_grass::board::map_generator::MapGenerator;
+use codingame_keep_off_the_grass::board::Owner;
+
+const MAX_TURNS: u32 = 200;
+/// The game ends early when no field changes scrap amount or owner for this many turns
+const MAX_IDLE_TURNS: u32 = 20;
+/// How long a bot gets to answer, counted from when the referee sent the turn
+const FIRST_TURN_TIMEOUT: Duration = Duration::from_millis(1000);
+const TURN_TIMEOUT: Duration = Duration::from_millis(50);
+
+struct Player {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    /// The lines the bot prints, read on their own thread so a hung bot can't hang the referee
+    lines: Receiver<String>,
+}
+
+impl Player {
+    fn start(name: &str, command: &str, verbose: bool) -> io::Result<Self> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(if verbose { Stdio::inherit() } else { Stdio::null() })
+            .spawn()?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            // ends when the bot closes its output, or when the referee no longer listens
+            for line in stdout.lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            name: name.to_string(),
+            child,
+            stdin,
+            lines,
+        })
+    }
+
+    fn send(&mut self, input: &str) -> io::Result<()> {
+        self.stdin.write_all(input.as_bytes())?;
+        self.stdin.flush()
+    }
+
+    /// Reads the actions of one turn, `None` when the bot stopped answering or missed `deadline`
+    ///
+    /// Output that can't be parsed counts as waiting for a turn.
+    fn read_actions(&mut self, deadline: Instant) -> Option<Vec<Action>> {
+        let line = match self.lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => {
+                eprintln!("{} timed out", self.name);
+                return None;
+            },
+            Err(RecvTimeoutError::Disconnected) => return None,
+        };
+
+        Some(Action::parse_turn(&line).unwrap_or_else(|e| {
+            eprintln!("{} sent invalid output ({}): {}", self.name, e, line.trim());
+            Vec::new()
+        }))
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn main() {
+    let usage = || -> ! {
+        eprintln!("usage: referee [--verbose] [--seed <seed>] <command of player 1> <command of player 2>");
+        process::exit(2);
+    };
+
+    let mut verbose = false;
+    let mut seed = None;
+    let mut commands = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--verbose" => verbose = true,
+            "--seed" => seed = Some(args.next().and_then(|x| x.parse::<u64>().ok()).unwrap_or_else(|| usage())),
+            _ => commands.push(arg),
+        }
+    }
+
+    if commands.len() != 2 {
+        usage();
+    }
+
+    let seed = seed.unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64);
+    println!("seed {}", seed);
+
+    let start = |name: &str, command: &str| Player::start(name, command, verbose).unwrap_or_else(|e| {
+        eprintln!("could not start {}: {}", name, e);
+        process::exit(2);
+    });
+    let mut players = [start("player 1", &commands[0]), start("player 2", &commands[1])];
+
+    let mut board = MapGenerator::new(seed).generate();
+    for player in players.iter_mut() {
+        // a bot that already quit will be noticed when reading its first turn
+        let _ = player.send(&format!("{} {}\n", board.width, board.height));
+    }
+
+    let mut turn = 0;
+    let mut idle_turns = 0;
+    while turn < MAX_TURNS {
+        turn += 1;
+
+        let _ = players[0].send(&board.to_input());
+        let _ = players[1].send(&board.swap_perspective().to_input());
+
+        let deadline = Instant::now() + if turn == 1 { FIRST_TURN_TIMEOUT } else { TURN_TIMEOUT };
+        let (my_actions, opponent_actions) = match (players[0].read_actions(deadline), players[1].read_actions(deadline)) {
+            (Some(a), Some(b)) => (a, b),
+            (a, b) => {
+                let result = match (a, b) {
+                    (Some(_), None) => format!("{} wins", players[0].name),
+                    (None, Some(_)) => format!("{} wins", players[1].name),
+                    _ => "draw".to_string(),
+                };
+                println!("{} after {} turns: a player stopped responding or timed out", result, turn);
+                return;
+            }
+        };
+
+        let next = board.apply_turn(&my_actions, &opponent_actions);
+
+        let changed = board.fields
+            .iter()
+            .zip(next.fields.iter())
+            .any(|(a, b)| a.scrap_amount != b.scrap_amount || a.owner != b.owner);
+        idle_turns = if changed { 0 } else { idle_turns + 1 };
+        board = next;
+
+        if board.territory_count(Owner::Me) == 0 || board.territory_count(Owner::Opponent) == 0 || idle_turns >= MAX_IDLE_TURNS {
+            break;
+        }
+    }
+
+    let scores = [board.territory_count(Owner::Me), board.territory_count(Owner::Opponent)];
+    let result = match scores[0].cmp(&scores[1]) {
+        std::cmp::Ordering::Greater => format!("{} wins", players[0].name),
+        std::cmp::Ordering::Less => format!("{} wins", players[1].name),
+        std::cmp::Ordering::Equal => "draw".to_string(),
+    };
+    println!("{} after {} turns: {} - {}", result, turn, scores[0], scores[1]);
+}
@@ -0,0 +1,91 @@
+//! Runs an agent on turns recorded by the `Recorder` and prints its actions
+//!
+//! Usage: `replay [--agent <name>] [--turn <turn>] [--render <ansi|plain>] <log file, or - for stdin>`
+//!
+//! The agent plays every recorded turn in order, up to the given turn, so it has the same history
+//! as in the game; only the given turn is printed.
+//! With `--render`, every turn's board is drawn before the actions.
+
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+use std::time::Instant;
+
+use codingame_keep_off_the_grass::action::Action;
+use codingame_keep_off_the_grass::agent::{self, TurnHistory};
+use codingame_keep_off_the_grass::board::render::Renderer;
+use codingame_keep_off_the_grass::recorder::Recording;
+
+fn main() {
+    let usage = || -> ! {
+        eprintln!("usage: replay [--agent <name>] [--turn <turn>] [--render <ansi|plain>] <log file, or - for stdin>");
+        process::exit(2);
+    };
+
+    let mut agent_name = "simple_economy".to_string();
+    let mut turn = None;
+    let mut renderer = None;
+    let mut path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--agent" => agent_name = args.next().unwrap_or_else(|| usage()),
+            "--turn" => turn = Some(args.next().and_then(|x| x.parse::<u32>().ok()).unwrap_or_else(|| usage())),
+            "--render" => renderer = match args.next().as_deref() {
+                Some("ansi") => Some(Renderer::ansi()),
+                Some("plain") => Some(Renderer::plain()),
+                _ => usage(),
+            },
+            _ => path = Some(arg),
+        }
+    }
+
+    let path = path.unwrap_or_else(|| usage());
+    let log = if path == "-" {
+        let mut log = String::new();
+        io::stdin().read_to_string(&mut log).map(|_| log)
+    } else {
+        fs::read_to_string(&path)
+    };
+    let log = log.unwrap_or_else(|e| {
+        eprintln!("could not read {}: {}", path, e);
+        process::exit(1);
+    });
+
+    let recording = Recording::decode(&log).unwrap_or_else(|e| {
+        eprintln!("could not decode {}: {}", path, e);
+        process::exit(1);
+    });
+
+    let mut agent = agent::from_name(&agent_name).unwrap_or_else(|| {
+        eprintln!("unknown agent {}", agent_name);
+        process::exit(2);
+    });
+
+    if !recording.turns.iter().any(|(x, _)| turn.is_none_or(|turn| turn == *x)) {
+        eprintln!("no recorded turns to replay");
+        process::exit(1);
+    }
+
+    let mut history = TurnHistory::default();
+    for (recorded_turn, board) in recording.turns.iter() {
+        if turn.is_some_and(|turn| *recorded_turn > turn) {
+            break;
+        }
+
+        let actions = history.play(agent.as_mut(), board, Instant::now());
+        if turn.is_none_or(|turn| turn == *recorded_turn) {
+            if let Some(renderer) = renderer {
+                print!("{}", renderer.render_board(board));
+            }
+            let printed = actions.iter().map(Action::to_string).collect::<Vec<_>>();
+            println!("turn {}: {}", recorded_turn, printed.join(";"));
+        }
+        history.push(board.clone(), actions);
+    }
+
+    if let Some(board) = history.boards.last() {
+        agent.on_game_end(board);
+    }
+}
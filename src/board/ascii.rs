@@ -0,0 +1,207 @@
+//! A text notation for boards, to build and check them in tests and tools
+//!
+//! ```text
+//! my_matter 20
+//! opponent_matter 10
+//! .  5m2 5m   4
+//! 3  6mR 4o1  .
+//! ```
+//!
+//! The header lines are optional and default to 0 matter.
+//! Every following line is a row, with one token per field, separated by whitespace:
+//! the scrap amount (`.` for grass), then optionally `m` or `o` for the owner
+//! (`n` for neutral, only needed before a number of units), the number of units and `R` for a recycler.
+//! `can_build`, `can_spawn` and `in_recycler_range` are derived from the other values,
+//! so boards round-trip through `to_ascii` when those flags are consistent.
+
+use super::boardbuilder::InputError;
+use super::{Board, Field, Owner};
+
+impl Board {
+
+    /// Errors report the line of the text, and the token on that line as column
+    pub fn from_ascii(text: &str) -> Result<Self, InputError> {
+        let mut my_matter = 0;
+        let mut opponent_matter = 0;
+        let mut rows: Vec<Vec<Field>> = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+            match tokens.first() {
+                None => continue,
+                Some(&"my_matter") => my_matter = parse_matter(&tokens).map_err(|e| e.on_line(line_number))?,
+                Some(&"opponent_matter") => opponent_matter = parse_matter(&tokens).map_err(|e| e.on_line(line_number))?,
+                Some(_) => {
+                    let y = rows.len() as u32;
+                    let row = tokens
+                        .iter()
+                        .enumerate()
+                        .map(|(x, token)| parse_field(token, x as u32, y).map_err(|e| e.on_line(line_number)))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    if rows.first().is_some_and(|first| first.len() != row.len()) {
+                        return Err(InputError::invalid(row.len(), "rows differ in length").on_line(line_number));
+                    }
+                    rows.push(row);
+                },
+            }
+        }
+
+        let mut board = Board {
+            width: rows.first().map_or(0, |x| x.len() as u32),
+            height: rows.len() as u32,
+            my_matter,
+            opponent_matter,
+            fields: rows.into_iter().flatten().collect(),
+        };
+        board.update_recycler_range();
+        board.update_build_spawn_flags();
+
+        Ok(board)
+    }
+
+    pub fn to_ascii(&self) -> String {
+        let tokens = self.fields
+            .iter()
+            .map(field_token)
+            .collect::<Vec<_>>();
+        let token_width = tokens.iter().map(|x| x.len()).max().unwrap_or(0);
+
+        let mut result = format!("my_matter {}\nopponent_matter {}\n", self.my_matter, self.opponent_matter);
+        for row in tokens.chunks(self.width.max(1) as usize) {
+            let row = row
+                .iter()
+                .map(|x| format!("{:<width$}", x, width = token_width))
+                .collect::<Vec<_>>();
+            result.push_str(row.join(" ").trim_end());
+            result.push('\n');
+        }
+
+        result
+    }
+}
+
+fn parse_matter(tokens: &[&str]) -> Result<u32, InputError> {
+    if tokens.len() != 2 {
+        return Err(InputError::invalid(tokens.len(), "expected a single matter amount"));
+    }
+    tokens[1].parse().map_err(|_| InputError::invalid(2, format!("invalid matter {}", tokens[1])))
+}
+
+fn parse_field(token: &str, x: u32, y: u32) -> Result<Field, InputError> {
+    let column = x as usize + 1;
+    let invalid = || InputError::invalid(column, format!("invalid field {}", token));
+
+    let (scrap_amount, rest) = match token.strip_prefix('.') {
+        Some(rest) => (0, rest),
+        None => {
+            let digits = token.find(|c: char| !c.is_ascii_digit()).unwrap_or(token.len());
+            (token[..digits].parse::<u32>().map_err(|_| invalid())?, &token[digits..])
+        },
+    };
+
+    let (owner, rest) = match rest.chars().next() {
+        Some('m') => (Owner::Me, &rest[1..]),
+        Some('o') => (Owner::Opponent, &rest[1..]),
+        Some('n') => (Owner::Neutral, &rest[1..]),
+        _ => (Owner::Neutral, rest),
+    };
+
+    let (has_recycler, rest) = match rest.strip_suffix('R') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+
+    let num_units = if rest.is_empty() { 0 } else { rest.parse::<u32>().map_err(|_| invalid())? };
+
+    Ok(Field {
+        x,
+        y,
+        scrap_amount,
+        owner,
+        num_units,
+        has_recycler,
+        ..Default::default()
+    })
+}
+
+pub(super) fn field_token(field: &Field) -> String {
+    let mut token = if field.scrap_amount == 0 { ".".to_string() } else { field.scrap_amount.to_string() };
+
+    match field.owner {
+        Owner::Me => token.push('m'),
+        Owner::Opponent => token.push('o'),
+        Owner::Neutral if field.num_units > 0 => token.push('n'),
+        Owner::Neutral => (),
+    }
+    if field.num_units > 0 {
+        token.push_str(&field.num_units.to_string());
+    }
+    if field.has_recycler {
+        token.push('R');
+    }
+
+    token
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::action::Action;
+    use super::super::map_generator::MapGenerator;
+
+    fn input_lines(board: &Board) -> Vec<String> {
+        board.fields.iter().map(|x| x.to_input_line()).collect()
+    }
+
+    /// A recycler on the first free field of `owner`
+    fn build(board: &Board, owner: Owner) -> Vec<Action> {
+        board.fields
+            .iter()
+            .find(|x| x.owner == owner && !x.is_grass() && !x.has_recycler && x.num_units == 0)
+            .map(|x| vec![Action::Build(x.x, x.y)])
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn simulated_boards_round_trip() {
+        for seed in 0..10 {
+            let mut board = MapGenerator::new(seed).generate();
+            for _ in 0..8 {
+                let text = board.to_ascii();
+                let parsed = Board::from_ascii(&text).unwrap();
+                assert_eq!(parsed.to_ascii(), text);
+                assert_eq!(input_lines(&parsed), input_lines(&board));
+                assert_eq!((parsed.my_matter, parsed.opponent_matter), (board.my_matter, board.opponent_matter));
+
+                board = board.apply_turn(&build(&board, Owner::Me), &build(&board, Owner::Opponent));
+            }
+        }
+    }
+
+    #[test]
+    fn tokens_with_owners_units_and_recyclers() {
+        let board = Board::from_ascii("
+            7n3 .mR 12o4 5
+        ").unwrap();
+
+        assert_eq!((board.fields[0].owner, board.fields[0].num_units), (Owner::Neutral, 3));
+        assert!(board.fields[1].is_grass() && board.fields[1].has_recycler);
+        assert_eq!((board.fields[2].scrap_amount, board.fields[2].owner, board.fields[2].num_units), (12, Owner::Opponent, 4));
+        assert!(!board.fields[3].in_recycler_range && board.fields[2].in_recycler_range);
+        assert_eq!(board.to_ascii(), "my_matter 0\nopponent_matter 0\n7n3  .mR  12o4 5\n");
+    }
+
+    #[test]
+    fn rows_of_different_lengths_are_rejected() {
+        match Board::from_ascii("\n5 5 5\n5 5\n") {
+            Err(InputError::Invalid { line, column, message }) => {
+                assert_eq!((line, column, message.as_str()), (3, 2, "rows differ in length"));
+            },
+            other => panic!("expected an invalid row, got {:?}", other.map(|x| x.to_ascii())),
+        }
+        assert!(Board::from_ascii("5 5x").is_err());
+    }
+}
@@ -1,4 +1,7 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::io;
+use std::io::BufRead;
 use super::Board;
 use super::Field;
 
@@ -23,33 +26,59 @@ impl BoardBuilder<SizeKnown> {
         }
     }
 
-    pub fn fields_from_stdin(mut self) -> BoardBuilder<Complete> {
+    /// Reads the `width height` line the referee sends before the first turn
+    pub fn size_from_reader<R: BufRead>(reader: &mut R) -> Result<Self, InputError> {
+        let input_line = read_line(reader)?;
+        let inputs = input_line.split_whitespace().collect::<Vec<_>>();
 
-        let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
-        let inputs = input_line.split(" ").collect::<Vec<_>>();
+        let width = parse_value(&inputs, 0).map_err(|e| e.on_line(1))?;
+        let height = parse_value(&inputs, 1).map_err(|e| e.on_line(1))?;
 
-        let my_matter = parse_input!(inputs[0], u32);
-        let opponent_matter = parse_input!(inputs[1], u32);
+        Ok(Self::new(width, height))
+    }
+
+    pub fn fields_from_stdin(self) -> Result<BoardBuilder<Complete>, InputError> {
+        self.fields_from_reader(&mut io::stdin().lock())
+    }
+
+    /// Reads the input of one turn: the matter line, followed by a line for every field
+    ///
+    /// Line numbers in errors count from the matter line, which is line 1.
+    pub fn fields_from_reader<R: BufRead>(mut self, reader: &mut R) -> Result<BoardBuilder<Complete>, InputError> {
+        let input_line = read_line(reader)?;
+        let inputs = input_line.split_whitespace().collect::<Vec<_>>();
+
+        let my_matter = parse_value(&inputs, 0).map_err(|e| e.on_line(1))?;
+        let opponent_matter = parse_value(&inputs, 1).map_err(|e| e.on_line(1))?;
 
         for i in 0..self.height as usize {
             for j in 0..self.width as usize {
-                let mut input_line = String::new();
-                io::stdin().read_line(&mut input_line).unwrap();
-                let inputs = input_line.trim().split([' ', '\n']).collect::<Vec<_>>();
+                let input_line = read_line(reader)?;
+                let inputs = input_line.split_whitespace().collect::<Vec<_>>();
 
-                self.fields.push(Field::from_input_line(&inputs, j as u32, i as u32));
+                let line = 2 + j + i * self.width as usize;
+                self.fields.push(Field::from_input_line(&inputs, j as u32, i as u32).map_err(|e| e.on_line(line))?);
             }
         }
 
-        BoardBuilder {
+        Ok(BoardBuilder {
             width: self.width,
             height: self.height,
             my_matter,
             opponent_matter,
             fields:  self.fields,
             _marker: Default::default(),
-        }
+        })
+    }
+}
+
+impl<S: BoardBuilderState> BoardBuilder<S> {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
     }
 }
 
@@ -72,3 +101,113 @@ pub trait BoardBuilderState {}
 
 impl BoardBuilderState for SizeKnown {}
 impl BoardBuilderState for Complete {}
+
+
+#[derive(Debug)]
+pub enum InputError {
+    /// The input ended, e.g. because the referee closed the pipe
+    Eof,
+    Io(io::Error),
+    /// `line` and `column` start at 1, `column` counts the values on the line
+    Invalid {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl InputError {
+    pub fn invalid(column: usize, message: impl Into<String>) -> Self {
+        InputError::Invalid {
+            line: 0,
+            column,
+            message: message.into(),
+        }
+    }
+
+    pub fn on_line(self, line: usize) -> Self {
+        match self {
+            InputError::Invalid { column, message, .. } => InputError::Invalid { line, column, message },
+            other => other,
+        }
+    }
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::Eof => write!(f, "end of input"),
+            InputError::Io(e) => write!(f, "could not read input: {}", e),
+            InputError::Invalid { line, column, message } => write!(f, "line {} column {}: {}", line, column, message),
+        }
+    }
+}
+
+impl Error for InputError {}
+
+impl From<io::Error> for InputError {
+    fn from(e: io::Error) -> Self {
+        InputError::Io(e)
+    }
+}
+
+fn read_line<R: BufRead>(reader: &mut R) -> Result<String, InputError> {
+    let mut input_line = String::new();
+    match reader.read_line(&mut input_line)? {
+        0 => Err(InputError::Eof),
+        _ => Ok(input_line),
+    }
+}
+
+/// Parses the value at `index` on a line, errors are reported on line 0
+pub fn parse_value<T: std::str::FromStr>(inputs: &[&str], index: usize) -> Result<T, InputError> {
+    let token = inputs.get(index).ok_or_else(|| InputError::invalid(index + 1, "missing value"))?;
+    token.parse::<T>().map_err(|_| InputError::invalid(index + 1, format!("invalid value {}", token)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Owner;
+
+    fn read_turn(width: u32, height: u32, input: &str) -> Result<Board, InputError> {
+        BoardBuilder::new(width, height)
+            .fields_from_reader(&mut input.as_bytes())
+            .map(|x| x.build())
+    }
+
+    fn position(error: InputError) -> (usize, usize) {
+        match error {
+            InputError::Invalid { line, column, .. } => (line, column),
+            other => panic!("expected an invalid value, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_the_size_and_a_turn() {
+        let builder = BoardBuilder::size_from_reader(&mut "2 1\n".as_bytes()).unwrap();
+        assert_eq!((builder.width(), builder.height()), (2, 1));
+
+        let board = read_turn(2, 1, "10 20\n4 1 2 0 0 1 0\n0 -1 0 0 0 0 0\n").unwrap();
+        assert_eq!((board.my_matter, board.opponent_matter), (10, 20));
+        assert_eq!((board.fields[0].owner, board.fields[0].num_units), (Owner::Me, 2));
+        assert!(board.fields[1].is_grass());
+    }
+
+    #[test]
+    fn input_that_ends_early_is_eof() {
+        assert!(matches!(BoardBuilder::size_from_reader(&mut "".as_bytes()), Err(InputError::Eof)));
+        assert!(matches!(read_turn(2, 1, ""), Err(InputError::Eof)));
+        // one field short
+        assert!(matches!(read_turn(2, 1, "10 20\n1 0 0 0 0 0 0\n"), Err(InputError::Eof)));
+    }
+
+    #[test]
+    fn invalid_values_report_line_and_column() {
+        assert_eq!(position(BoardBuilder::size_from_reader(&mut "12 x\n".as_bytes()).err().unwrap()), (1, 2));
+        assert_eq!(position(read_turn(2, 1, "10\n").unwrap_err()), (1, 2));
+        assert_eq!(position(read_turn(2, 1, "10 20\n1 0 0 0 0 0 0\n1 x 0 0 0 0 0\n").unwrap_err()), (3, 2));
+        assert_eq!(position(read_turn(2, 1, "10 20\n1 0 0\n").unwrap_err()), (2, 4));
+        assert_eq!(position(read_turn(2, 1, "10 20\n1 7 0 0 0 0 0\n").unwrap_err()), (2, 2));
+    }
+}
@@ -0,0 +1,196 @@
+use super::{Board, Coord, Grid, Owner};
+
+/// Finds the fields where a recycler cuts the opponent off from territory we can then take
+///
+/// A recycler blocks its own field straight away, and the fields around it with no more scrap
+/// than its own turn to grass before it runs out. Both are taken out of the map to see
+/// which islands are left without the opponent.
+#[derive(Clone, Debug)]
+pub struct ChokepointBoard {
+    /// Fields that split the traversible map in two when they are taken out on their own
+    pub articulation_points: Grid<bool>,
+    /// For every field we can build on, the change in the number of fields on islands
+    /// only we can reach, once the recycler has mined out
+    pub sealed_territory: Grid<Option<i32>>,
+}
+
+impl ChokepointBoard {
+
+    pub fn new(board: &Board) -> Self {
+        let articulation_points = articulation_points(board);
+        let removed = Grid::filled(board.width, board.height, false);
+        let sealed_now = sealed_fields(board, &removed);
+
+        let mut removed = removed;
+        let sealed_territory = Grid::from_board(board, |field| {
+            if field.owner != Owner::Me || !field.is_traversible() || field.num_units > 0 {
+                return None;
+            }
+
+            let cut = field.coord()
+                .in_range(board.width, board.height)
+                .filter(|c| c.index(board.width) == field.coord().index(board.width)
+                    || board.fields[c.index(board.width)].scrap_amount <= field.scrap_amount)
+                .collect::<Vec<_>>();
+
+            // Taking out a single field only matters if it is an articulation point
+            if cut.len() == 1 && !articulation_points[field.coord()] {
+                return Some(0);
+            }
+
+            for coord in cut.iter() {
+                removed[*coord] = true;
+            }
+            let sealed = sealed_fields(board, &removed) as i32 - sealed_now as i32;
+            for coord in cut.iter() {
+                removed[*coord] = false;
+            }
+
+            Some(sealed)
+        });
+
+        Self {
+            articulation_points,
+            sealed_territory,
+        }
+    }
+
+    pub fn get_field(&self, x: u32, y: u32) -> Option<i32> {
+        self.sealed_territory.get(Coord::new(x, y)).copied().flatten()
+    }
+}
+
+/// The number of traversible fields on islands where we own a field and the opponent doesn't
+fn sealed_fields(board: &Board, removed: &Grid<bool>) -> u32 {
+    let open = |c: Coord| board.fields[c.index(board.width)].is_traversible() && !removed[c];
+    let mut visited = Grid::filled(board.width, board.height, false);
+    let mut total = 0;
+
+    for start in board.fields.iter().map(|x| x.coord()) {
+        if visited[start] || !open(start) {
+            continue;
+        }
+
+        let (mut size, mut mine, mut theirs) = (0, false, false);
+        let mut stack = vec![start];
+        visited[start] = true;
+        while let Some(coord) = stack.pop() {
+            size += 1;
+            match board.fields[coord.index(board.width)].owner {
+                Owner::Me => mine = true,
+                Owner::Opponent => theirs = true,
+                Owner::Neutral => (),
+            }
+
+            for neighbour in coord.neighbours(board.width, board.height) {
+                if !visited[neighbour] && open(neighbour) {
+                    visited[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+
+        if mine && !theirs {
+            total += size;
+        }
+    }
+
+    total
+}
+
+/// Tarjan's articulation points over the traversible fields, without recursion
+fn articulation_points(board: &Board) -> Grid<bool> {
+    let (width, height) = (board.width, board.height);
+    let traversible = |c: Coord| board.fields[c.index(width)].is_traversible();
+
+    let mut result = Grid::filled(width, height, false);
+    let mut discovery = Grid::filled(width, height, u32::MAX);
+    let mut low = Grid::filled(width, height, 0);
+    let mut time = 0;
+
+    for root in board.fields.iter().map(|x| x.coord()) {
+        if discovery[root] != u32::MAX || !traversible(root) {
+            continue;
+        }
+
+        discovery[root] = time;
+        low[root] = time;
+        time += 1;
+        let mut root_children = 0;
+
+        // (field, parent, neighbours still to visit)
+        let mut stack = vec![(root, None, root.neighbours(width, height))];
+        while let Some((coord, parent, neighbours)) = stack.last_mut() {
+            let (coord, parent) = (*coord, *parent);
+            match neighbours.next() {
+                Some(next) if !traversible(next) || Some(next) == parent => (),
+                Some(next) if discovery[next] != u32::MAX => {
+                    low[coord] = low[coord].min(discovery[next]);
+                },
+                Some(next) => {
+                    discovery[next] = time;
+                    low[next] = time;
+                    time += 1;
+                    if coord == root {
+                        root_children += 1;
+                    }
+                    stack.push((next, Some(coord), next.neighbours(width, height)));
+                },
+                None => {
+                    stack.pop();
+                    if let Some(parent) = parent {
+                        low[parent] = low[parent].min(low[coord]);
+                        if parent != root && low[coord] >= discovery[parent] {
+                            result[parent] = true;
+                        }
+                    }
+                },
+            }
+        }
+
+        result[root] = root_children > 1;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_articulation_points() {
+        let board = Board::from_ascii("
+            5 5 . 5 5
+            5 5 5 5 5
+            5 5 . . 5
+        ").unwrap();
+        let points = ChokepointBoard::new(&board).articulation_points;
+
+        let found = points
+            .enumerate()
+            .filter(|(_, x)| **x)
+            .map(|(c, _)| c)
+            .collect::<Vec<_>>();
+        // every field of the middle row but the leftmost holds a part of the map to the rest
+        assert_eq!(found, vec![Coord::new(1, 1), Coord::new(2, 1), Coord::new(3, 1), Coord::new(4, 1)]);
+    }
+
+    #[test]
+    fn scores_recyclers_by_the_territory_they_seal() {
+        let board = Board::from_ascii("
+            5m1 5m 5 . 5 5o1
+            5m  5m 9m 10 5 5o
+            5m  5m 5 . 5 5o
+        ").unwrap();
+        let chokepoints = ChokepointBoard::new(&board);
+
+        // (3, 1) is the only way across; it has more scrap than (2, 1) so it stays,
+        // but the fields around (2, 1) turn to grass and cut off the five fields left of them
+        assert_eq!(chokepoints.get_field(2, 1), Some(5));
+        // mining out (1, 1) leaves two of our fields on their own
+        assert_eq!(chokepoints.get_field(1, 1), Some(2));
+        assert_eq!(chokepoints.get_field(4, 1), None);
+        assert_eq!(chokepoints.get_field(0, 0), None);
+    }
+}
@@ -0,0 +1,150 @@
+/// A position on the board
+///
+/// All neighbour lookups go through `Coord`, so they are checked against the board size
+/// instead of wrapping around to the next row or underflowing at 0.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Coord {
+    pub x: u32,
+    pub y: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West]; // NESW
+}
+
+impl Coord {
+
+    pub fn new(x: u32, y: u32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn from_index(index: usize, width: u32) -> Self {
+        Self {
+            x: index as u32 % width,
+            y: index as u32 / width,
+        }
+    }
+
+    pub fn index(self, width: u32) -> usize {
+        (self.x + self.y * width) as usize
+    }
+
+    pub fn in_bounds(self, width: u32, height: u32) -> bool {
+        self.x < width && self.y < height
+    }
+
+    /// The neighbour in `direction`, if it is on the board
+    pub fn step(self, direction: Direction, width: u32, height: u32) -> Option<Self> {
+        if !self.in_bounds(width, height) {
+            return None;
+        }
+
+        let neighbour = match direction {
+            Direction::North => Self::new(self.x, self.y.checked_sub(1)?),
+            Direction::East => Self::new(self.x + 1, self.y),
+            Direction::South => Self::new(self.x, self.y + 1),
+            Direction::West => Self::new(self.x.checked_sub(1)?, self.y),
+        };
+
+        if neighbour.in_bounds(width, height) { Some(neighbour) } else { None }
+    }
+
+    /// The neighbours in NESW order, `None` where that side is off the board
+    pub fn adjacent(self, width: u32, height: u32) -> [Option<Self>; 4] {
+        Direction::ALL.map(|direction| self.step(direction, width, height))
+    }
+
+    /// The neighbours that are on the board, in NESW order
+    pub fn neighbours(self, width: u32, height: u32) -> impl Iterator<Item = Self> + Clone {
+        self.adjacent(width, height).into_iter().flatten()
+    }
+
+    /// The coord itself followed by its neighbours on the board: the fields a recycler here harvests
+    pub fn in_range(self, width: u32, height: u32) -> impl Iterator<Item = Self> {
+        std::iter::once(self)
+            .filter(move |x| x.in_bounds(width, height))
+            .chain(self.neighbours(width, height))
+    }
+
+    pub fn manhattan_distance(self, other: Self) -> u32 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// The direction of an adjacent coord
+    pub fn direction_to(self, other: Self) -> Option<Direction> {
+        match (other.x as i64 - self.x as i64, other.y as i64 - self.y as i64) {
+            (0, -1) => Some(Direction::North),
+            (1, 0) => Some(Direction::East),
+            (0, 1) => Some(Direction::South),
+            (-1, 0) => Some(Direction::West),
+            _ => None,
+        }
+    }
+}
+
+impl From<(u32, u32)> for Coord {
+    fn from((x, y): (u32, u32)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<Coord> for (u32, u32) {
+    fn from(coord: Coord) -> Self {
+        (coord.x, coord.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn neighbours(x: u32, y: u32) -> Vec<(u32, u32)> {
+        Coord::new(x, y).neighbours(4, 3).map(|x| x.into()).collect()
+    }
+
+    #[test]
+    fn corners_have_two_neighbours() {
+        assert_eq!(neighbours(0, 0), vec![(1, 0), (0, 1)]);
+        assert_eq!(neighbours(3, 0), vec![(3, 1), (2, 0)]);
+        assert_eq!(neighbours(3, 2), vec![(3, 1), (2, 2)]);
+        assert_eq!(neighbours(0, 2), vec![(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn edges_have_three_neighbours() {
+        assert_eq!(neighbours(1, 0), vec![(2, 0), (1, 1), (0, 0)]);
+        assert_eq!(neighbours(3, 1), vec![(3, 0), (3, 2), (2, 1)]);
+        assert_eq!(neighbours(2, 2), vec![(2, 1), (3, 2), (1, 2)]);
+        assert_eq!(neighbours(0, 1), vec![(0, 0), (1, 1), (0, 2)]);
+    }
+
+    #[test]
+    fn adjacent_keeps_nesw_slots() {
+        let adjacent = Coord::new(3, 0).adjacent(4, 3);
+        assert_eq!(adjacent, [None, None, Some(Coord::new(3, 1)), Some(Coord::new(2, 0))]);
+
+        assert_eq!(Coord::new(1, 1).adjacent(4, 3).iter().flatten().count(), 4);
+    }
+
+    #[test]
+    fn off_board_coords_have_no_neighbours() {
+        assert_eq!(neighbours(4, 1), vec![]);
+        assert_eq!(Coord::new(0, 3).in_range(4, 3).count(), 0);
+    }
+
+    #[test]
+    fn index_round_trips() {
+        for index in 0..12 {
+            assert_eq!(Coord::from_index(index, 4).index(4), index);
+        }
+        assert_eq!(Coord::from_index(7, 4), Coord::new(3, 1));
+    }
+}
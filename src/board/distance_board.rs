@@ -1,14 +1,12 @@
 use std::cmp::{Ordering, Reverse};
-use std::collections::{BinaryHeap, HashSet};
-use std::hash::{BuildHasherDefault, Hasher};
-use super::adjacent_in_range;
-use super::{Board, Owner};
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt::{Display, Formatter};
+use super::mine_duration_board::MineDurationBoard;
+use super::{Board, Coord, Grid, Owner};
 
 #[derive(Clone, Debug)]
 pub struct DistanceBoard {
-    pub width: u32,
-    pub height: u32,
-    pub distances: Vec<ManhattanDistance>,
+    pub distances: Grid<ManhattanDistance>,
 }
 
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
@@ -19,10 +17,7 @@ pub enum ManhattanDistance {
 
 impl ManhattanDistance {
     pub fn is_unreachable(self) -> bool {
-        match self {
-            ManhattanDistance::Unreachable => true,
-            _ => false,
-        }
+        matches!(self, ManhattanDistance::Unreachable)
     }
 
     pub fn distance_or_panic(self) -> u32 {
@@ -33,144 +28,484 @@ impl ManhattanDistance {
     }
 }
 
+impl Display for ManhattanDistance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManhattanDistance::Dist(x) => write!(f, "{}", x),
+            ManhattanDistance::Unreachable => write!(f, "-"),
+        }
+    }
+}
 
 impl DistanceBoard {
 
     pub fn from_owner(board: &Board, from_owner: Owner) -> Self {
-        // let mut calculated: FieldHashSet<(u32, u32)> = Default::default();
-        let mut distances = vec![ManhattanDistance::Unreachable; (board.width * board.height) as usize];
-
-        // (dist, x, y)
-        let mut frontier: BinaryHeap<(Reverse<u32>, u32, u32)> = BinaryHeap::new();
-
-        // initialize search frontier
-        // First only fields w/ robots
-        for i in 0..board.width {
-            for j in 0..board.height {
-                let current_field = board.get_field(i, j).unwrap();
-
-                match (current_field.owner, current_field.num_units) {
-                    (_, 0) => (),
-                    (owner, _) if owner == from_owner => {
-                        distances[(i + j * board.width) as usize] = ManhattanDistance::Dist(0);
-
-                        let adjacent_coords = adjacent_in_range(i, j, board.width, board.height);
-                        let adjacent_coords = adjacent_coords
-                            .into_iter()
-                            .flatten()
-                            .filter(|x| board.get_field(x.0, x.1).unwrap().is_traversible())
-                            .filter(|x| board.get_field(x.0, x.1).unwrap().owner != from_owner)
-                            .filter(|x| distances[(x.0 + x.1 * board.width) as usize ] == ManhattanDistance::Unreachable);
-
-                        frontier.extend(
-                            adjacent_coords.clone()
-                                .map(|x| (Reverse(1), x.0, x.1))
-                        );
-
-                        let adjacent_coords: Vec<_> = adjacent_coords.collect(); // end the borrow on `distances`
-                        for adjacent in adjacent_coords.into_iter() {
-                            distances[(adjacent.0 + adjacent.1 * board.width) as usize] = ManhattanDistance::Dist(1);
-                        }
-                    },
-                    _ => (),
+        DistanceSearch::default().from_owner(board, from_owner)
+    }
+
+    /// Like `from_owner`, but a field only counts as traversible if it still has scrap when a robot gets there
+    ///
+    /// A robot that arrives on a field after `t` moves, or is spawned there for `t = 1`,
+    /// is destroyed if the field turns to grass in the harvest of that same turn.
+    pub fn with_grass_forecast(board: &Board, from_owner: Owner, mine_duration_board: &MineDurationBoard) -> Self {
+        DistanceSearch::default().with_grass_forecast(board, from_owner, mine_duration_board)
+    }
+
+    /// Like `from_owner`, but only through the fields `traveller` has a distance for
+    ///
+    /// Used with a `with_grass_forecast` board to follow the distances towards `from_owner`
+    /// without routing over fields that are grass by the time the traveller gets there.
+    pub fn through(board: &Board, from_owner: Owner, traveller: &DistanceBoard) -> Self {
+        DistanceSearch::default().through(board, from_owner, traveller)
+    }
+
+    pub fn get_field(&self, x: u32, y: u32) -> Option<&ManhattanDistance> {
+        self.distances.get(Coord::new(x, y))
+    }
+
+    pub fn get_adjacent_fields(&self, width: u32, height: u32) -> [Option<&ManhattanDistance>; 4] { // NESW
+        self.distances.adjacent(Coord::new(width, height))
+    }
+
+    /// Finds all the directions which are best for going up or down the distancefield
+    pub fn towards(&self, x: u32, y: u32, ordering: Ordering) -> [bool; 4] {
+        let own_distance = self.distances[Coord::new(x, y)];
+
+        self.get_adjacent_fields(x, y)
+            .map(|x| x.is_some_and(|distance| distance.cmp(&own_distance) == ordering))
+    }
+
+    /// All neighbours one step closer to the nearest source, so every one of them is on a shortest path
+    ///
+    /// Empty on the sources themselves, and on unreachable fields.
+    pub fn next_steps(&self, coord: Coord) -> Vec<Coord> {
+        let ManhattanDistance::Dist(distance) = self.distances[coord] else {
+            return Vec::new();
+        };
+
+        coord.neighbours(self.distances.width(), self.distances.height())
+            .filter(|c| distance > 0 && self.distances[*c] == ManhattanDistance::Dist(distance - 1))
+            .collect()
+    }
+
+    /// A shortest path from `coord` to the nearest source, both included, `None` if there is none
+    ///
+    /// Sources are the fields a search starts from: robots, or other owned fields without a robot next to them.
+    pub fn path_to_source(&self, coord: Coord) -> Option<Vec<Coord>> {
+        if self.distances[coord].is_unreachable() {
+            return None;
+        }
+
+        let mut path = vec![coord];
+        while let Some(next) = self.next_steps(*path.last().unwrap()).first() {
+            path.push(*next);
+        }
+        Some(path)
+    }
+
+    /// For every field, how many of the shortest paths from `from` to the nearest sources go through it
+    pub fn shortest_path_counts(&self, from: Coord) -> Grid<u64> {
+        let mut through = Grid::filled(self.distances.width(), self.distances.height(), 0_u64);
+        let ManhattanDistance::Dist(start) = self.distances[from] else {
+            return through;
+        };
+
+        let mut order = self.distances
+            .enumerate()
+            .filter_map(|(c, d)| match d {
+                ManhattanDistance::Dist(d) if *d <= start => Some((*d, c)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        order.sort();
+
+        // paths from every field down to a source, from the sources up
+        let mut down = through.clone();
+        for (_, coord) in order.iter() {
+            let steps = self.next_steps(*coord);
+            down[*coord] = if steps.is_empty() { 1 } else { steps.iter().fold(0_u64, |a, c| a.saturating_add(down[*c])) };
+        }
+
+        // paths from `from` to every field, from `from` down
+        let mut up = through.clone();
+        up[from] = 1;
+        for (_, coord) in order.iter().rev() {
+            if up[*coord] > 0 {
+                for step in self.next_steps(*coord) {
+                    up[step] = up[step].saturating_add(up[*coord]);
                 }
             }
         }
 
-        // now initialize owned fields w/o robots and w/o recyclers
-        for i in 0..board.width {
-            for j in 0..board.height {
-                let current_field = board.get_field(i, j).unwrap();
-
-                match (current_field.owner, current_field.num_units, current_field.is_traversible()) {
-                    (owner, 0, true) if owner == from_owner => {
-                        distances[(i + j * board.width) as usize] = ManhattanDistance::Dist(1);
-
-                        let adjacent_coords = adjacent_in_range(i, j, board.width, board.height);
-                        let adjacent_coords = adjacent_coords
-                            .into_iter()
-                            .flatten()
-                            .filter(|x| board.get_field(x.0, x.1).unwrap().is_traversible())
-                            .filter(|x| board.get_field(x.0, x.1).unwrap().owner != from_owner)
-                            .filter(|x| distances[(x.0 + x.1 * board.width) as usize ] == ManhattanDistance::Unreachable);
-
-                        frontier.extend(
-                            adjacent_coords.clone()
-                                .map(|x| (Reverse(2), x.0, x.1))
-                        );
-
-                        let adjacent_coords: Vec<_> = adjacent_coords.collect(); // end the borrow on `distances`
-                        for adjacent in adjacent_coords.into_iter() {
-                            distances[(adjacent.0 + adjacent.1 * board.width) as usize] = ManhattanDistance::Dist(2);
-                        }
-                    },
-                    _ => (),
+        for coord in through.coords().collect::<Vec<_>>() {
+            through[coord] = up[coord].saturating_mul(down[coord]);
+        }
+        through
+    }
+
+    /// The cheapest path from `from` to `to` over traversible fields, both included, with its cost
+    ///
+    /// Every step costs what `weights` gives for the owner of the field it enters.
+    pub fn a_star(board: &Board, from: Coord, to: Coord, weights: &PathWeights) -> Option<(u32, Vec<Coord>)> {
+        let traversible = |c: Coord| board.fields[c.index(board.width)].is_traversible();
+        if !traversible(from) || !traversible(to) {
+            return None;
+        }
+
+        let cheapest_step = weights.mine.min(weights.neutral).min(weights.opponent);
+        let heuristic = |c: Coord| c.manhattan_distance(to) * cheapest_step;
+
+        let mut costs = Grid::filled(board.width, board.height, u32::MAX);
+        let mut came_from: Grid<Option<Coord>> = Grid::filled(board.width, board.height, None);
+        // (estimated total cost, cost so far, field index)
+        let mut frontier = BinaryHeap::new();
+        costs[from] = 0;
+        frontier.push(Reverse((heuristic(from), 0, from.index(board.width))));
+
+        while let Some(Reverse((_, cost, index))) = frontier.pop() {
+            let coord = Coord::from_index(index, board.width);
+            if coord == to {
+                let mut path = vec![to];
+                while let Some(previous) = came_from[*path.last().unwrap()] {
+                    path.push(previous);
+                }
+                path.reverse();
+                return Some((cost, path));
+            }
+            if cost > costs[coord] {
+                continue;
+            }
+
+            for neighbour in coord.neighbours(board.width, board.height).filter(|c| traversible(*c)) {
+                let next_cost = cost + weights.cost(board.fields[neighbour.index(board.width)].owner);
+                if next_cost < costs[neighbour] {
+                    costs[neighbour] = next_cost;
+                    came_from[neighbour] = Some(coord);
+                    frontier.push(Reverse((next_cost + heuristic(neighbour), next_cost, neighbour.index(board.width))));
                 }
             }
         }
 
+        None
+    }
+
+}
 
-        // Keep on rolling the frontier
-        while let Some((distance, x, y)) = frontier.pop() {
-            let adjacent_coords = adjacent_in_range(x, y, board.width, board.height);
-            let adjacent_coords = adjacent_coords
-                .into_iter()
-                .flatten()
-                .filter(|x| board.get_field(x.0, x.1).unwrap().is_traversible())
-                .filter(|x| distances[(x.0 + x.1 * board.width) as usize ] == ManhattanDistance::Unreachable);
+/// The cost of stepping onto a field, by its owner, for `DistanceBoard::a_star`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PathWeights {
+    pub mine: u32,
+    pub neutral: u32,
+    pub opponent: u32,
+}
 
-            frontier.extend(
-                adjacent_coords.clone()
-                    .map(|x| (Reverse(distance.0 + 1), x.0, x.1))
-            );
+impl PathWeights {
 
-            let adjacent_coords: Vec<_> = adjacent_coords.collect(); // end the borrow on `distances`
-            for adjacent in adjacent_coords.into_iter() {
-                distances[(adjacent.0 + adjacent.1 * board.width) as usize] = ManhattanDistance::Dist(distance.0 + 1);
-            }
+    pub fn cost(&self, owner: Owner) -> u32 {
+        match owner {
+            Owner::Me => self.mine,
+            Owner::Neutral => self.neutral,
+            Owner::Opponent => self.opponent,
+        }
+    }
+}
 
+impl Default for PathWeights {
+    /// Every step costs 1, so paths are as short as the distances of `DistanceBoard`
+    fn default() -> Self {
+        Self {
+            mine: 1,
+            neutral: 1,
+            opponent: 1,
         }
+    }
+}
+
+/// Runs the searches of `DistanceBoard`, keeping its queue around so repeated searches don't allocate it again
+///
+/// Every move costs one turn, so a breadth first search from all the starting fields at once
+/// gives the same distances as Dijkstra, in linear time.
+/// Boards handed back with `recycle` have their grids filled by the next searches, instead of new ones.
+#[derive(Clone, Debug, Default)]
+pub struct DistanceSearch {
+    queue: VecDeque<Coord>,
+    spare: Vec<Grid<ManhattanDistance>>,
+}
+
+impl DistanceSearch {
+
+    /// Hands back a board that is no longer needed, for a later search to reuse
+    pub fn recycle(&mut self, board: DistanceBoard) {
+        self.spare.push(board.distances);
+    }
+
+    pub fn from_owner(&mut self, board: &Board, from_owner: Owner) -> DistanceBoard {
+        self.search(board, from_owner, |_, _| true)
+    }
 
+    /// See `DistanceBoard::with_grass_forecast`
+    pub fn with_grass_forecast(&mut self, board: &Board, from_owner: Owner, mine_duration_board: &MineDurationBoard) -> DistanceBoard {
+        self.search(board, from_owner, |coord, distance| mine_duration_board.mine_durations[coord].survives(distance))
+    }
+
+    /// See `DistanceBoard::through`
+    pub fn through(&mut self, board: &Board, from_owner: Owner, traveller: &DistanceBoard) -> DistanceBoard {
+        self.search(board, from_owner, |coord, _| !traveller.distances[coord].is_unreachable())
+    }
+
+    /// Distances from the single field `from`, like `with_grass_forecast` for the robots on it
+    ///
+    /// The robots are on `from` after `departure` turns, and the distances count from now,
+    /// so a search from a field robots step onto first has a `departure` of 1.
+    pub fn from_coord_with_grass_forecast(&mut self, board: &Board, from: Coord, departure: u32, mine_duration_board: &MineDurationBoard) -> DistanceBoard {
+        let mut distances = self.unreachable_grid(board);
+        self.queue.clear();
+        distances[from] = ManhattanDistance::Dist(departure);
+        self.queue.push_back(from);
+
+        self.expand(board, &mut distances, |coord, distance| mine_duration_board.mine_durations[coord].survives(distance));
         DistanceBoard {
-            width: board.width,
-            height: board.height,
             distances,
         }
     }
 
-    pub fn get_field(&self, x: u32, y: u32) -> Option<&ManhattanDistance> {
-        self.distances.get((x + y * self.width) as usize)
+    /// Searches from the robots of `from_owner` at distance 0 and its other fields at 1,
+    /// entering only the traversible fields `passable` accepts at the distance they are reached
+    fn search(&mut self, board: &Board, from_owner: Owner, passable: impl Fn(Coord, u32) -> bool) -> DistanceBoard {
+        let mut distances = self.unreachable_grid(board);
+        self.queue.clear();
+
+        // all robots go before all other fields, so the queue stays sorted by distance
+        for field in board.fields.iter().filter(|x| x.owner == from_owner && x.num_units > 0) {
+            distances[field.coord()] = ManhattanDistance::Dist(0);
+            self.queue.push_back(field.coord());
+        }
+        for field in board.fields.iter().filter(|x| x.owner == from_owner && x.num_units == 0) {
+            if field.is_traversible() && passable(field.coord(), 1) {
+                distances[field.coord()] = ManhattanDistance::Dist(1);
+                self.queue.push_back(field.coord());
+            }
+        }
+
+        self.expand(board, &mut distances, passable);
+        DistanceBoard {
+            distances,
+        }
     }
 
-    pub fn get_adjacent_fields(&self, width: u32, height: u32) -> [Option<&ManhattanDistance>; 4] { // NESW
-        [
-            self.get_field(width, height - 1),
-            self.get_field(width + 1, height),
-            self.get_field(width, height + 1),
-            self.get_field(width - 1, height),
-        ]
+    /// A grid for `board` with every field unreachable, a recycled one if it has the right size
+    fn unreachable_grid(&mut self, board: &Board) -> Grid<ManhattanDistance> {
+        match self.spare.pop() {
+            Some(mut grid) if grid.width() == board.width && grid.height() == board.height => {
+                grid.iter_mut().for_each(|x| *x = ManhattanDistance::Unreachable);
+                grid
+            },
+            _ => Grid::filled(board.width, board.height, ManhattanDistance::Unreachable),
+        }
     }
 
-    /// Finds all the directions which are best for going up or down the distancefield
-    pub fn towards(&self, x: u32, y: u32, ordering: Ordering) -> [bool; 4] {
-        let own_distance = self.distances[(x + self.width * y) as usize];
-        let adjacent_fields = adjacent_in_range(x, y, self.width, self.height);
-
-        [
-            if let Some((x, y)) = adjacent_fields[0] {
-                self.distances[(x + self.width * y) as usize].cmp(&own_distance) == ordering
-            } else { false },
-            if let Some((x, y)) = adjacent_fields[1] {
-                self.distances[(x + self.width * y) as usize].cmp(&own_distance) == ordering
-            } else { false },
-            if let Some((x, y)) = adjacent_fields[2] {
-                self.distances[(x + self.width * y) as usize].cmp(&own_distance) == ordering
-            } else { false },
-            if let Some((x, y)) = adjacent_fields[3] {
-                self.distances[(x + self.width * y) as usize].cmp(&own_distance) == ordering
-            } else { false },
-        ]
+    /// Runs the search from the fields in the queue, which must be sorted by distance
+    fn expand(&mut self, board: &Board, distances: &mut Grid<ManhattanDistance>, passable: impl Fn(Coord, u32) -> bool) {
+        // fields only ever turn to grass, so the first time a field is reached is the only one that matters
+        while let Some(coord) = self.queue.pop_front() {
+            let distance = distances[coord].distance_or_panic() + 1;
+            for neighbour in coord.neighbours(board.width, board.height) {
+                if distances[neighbour].is_unreachable()
+                    && board.fields[neighbour.index(board.width)].is_traversible()
+                    && passable(neighbour, distance) {
+                    distances[neighbour] = ManhattanDistance::Dist(distance);
+                    self.queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+}
+
+/// The search `from_owner` used to run: Dijkstra from the robots at 0 and the other owned fields at 1
+///
+/// Kept as the reference for the tests and the benchmark example.
+#[doc(hidden)]
+pub fn heap_distances(board: &Board, from_owner: Owner) -> Grid<ManhattanDistance> {
+    let mut distances = Grid::filled(board.width, board.height, ManhattanDistance::Unreachable);
+    let mut frontier = BinaryHeap::new();
+    for field in board.fields.iter().filter(|x| x.owner == from_owner) {
+        if field.num_units > 0 {
+            frontier.push(Reverse((0, field.coord().index(board.width))));
+        } else if field.is_traversible() {
+            frontier.push(Reverse((1, field.coord().index(board.width))));
+        }
     }
 
+    while let Some(Reverse((distance, index))) = frontier.pop() {
+        let coord = Coord::from_index(index, board.width);
+        if !distances[coord].is_unreachable() {
+            continue;
+        }
+        distances[coord] = ManhattanDistance::Dist(distance);
+        for neighbour in coord.neighbours(board.width, board.height) {
+            if distances[neighbour].is_unreachable() && board.fields[neighbour.index(board.width)].is_traversible() {
+                frontier.push(Reverse((distance + 1, neighbour.index(board.width))));
+            }
+        }
+    }
+    distances
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::action::Action;
+    use super::super::super::rng::Rng;
+    use super::super::map_generator::MapGenerator;
+    use super::super::Field;
+
+    /// Every stack moves somewhere random, and a recycler and a robot go on random owned fields
+    fn random_turn(board: &Board, rng: &mut Rng) -> Vec<Action> {
+        let mut pick = |fields: Vec<&Field>| (!fields.is_empty()).then(|| fields[rng.range(0, fields.len() as u32 - 1) as usize].coord());
+        let mut result = Vec::new();
+        if let Some(c) = pick(board.fields.iter().filter(|x| x.can_build).collect()) {
+            result.push(Action::Build(c.x, c.y));
+        }
+        if let Some(c) = pick(board.fields.iter().filter(|x| x.can_spawn).collect()) {
+            result.push(Action::Spawn(1, c.x, c.y));
+        }
+        for field in board.fields.iter().filter(|x| x.owner == Owner::Me && x.num_units > 0) {
+            let to = (rng.range(0, board.width - 1), rng.range(0, board.height - 1));
+            result.push(Action::Move { amount: field.num_units, from: (field.x, field.y), to });
+        }
+        result
+    }
+
+    #[test]
+    fn breadth_first_search_matches_dijkstra_on_played_boards() {
+        let mut rng = Rng::new(19);
+        let mut search = DistanceSearch::default();
+        for seed in 0..10 {
+            let mut board = MapGenerator::new(seed).generate();
+            for _ in 0..40 {
+                for owner in [Owner::Me, Owner::Opponent, Owner::Neutral] {
+                    let distances = search.from_owner(&board, owner);
+                    assert_eq!(distances.distances, heap_distances(&board, owner));
+                    search.recycle(distances);
+                }
+
+                let my_actions = random_turn(&board, &mut rng);
+                let opponent_actions = random_turn(&board.swap_perspective(), &mut rng);
+                board = board.apply_turn(&my_actions, &opponent_actions);
+            }
+        }
+    }
+
+    #[test]
+    fn recycled_boards_are_cleared() {
+        let board = Board::from_ascii("
+            5m1 5 . 5o1
+            5   5 . 5
+        ").unwrap();
+        let mut search = DistanceSearch::default();
+
+        let mine = search.from_owner(&board, Owner::Me);
+        let fresh = DistanceBoard::from_owner(&board, Owner::Opponent);
+        search.recycle(mine);
+        assert_eq!(search.from_owner(&board, Owner::Opponent).distances, fresh.distances);
+        assert!(fresh.distances[Coord::new(0, 0)].is_unreachable());
+    }
+
+    #[test]
+    fn forecast_matches_the_frozen_map_without_recyclers() {
+        let board = Board::from_ascii("
+            5m2 5m 5 . 5
+            5   5  5 5 5o
+            5   .  5 5o3 5
+        ").unwrap();
+        let mine_duration_board = MineDurationBoard::new(&board);
+
+        for owner in [Owner::Me, Owner::Opponent, Owner::Neutral] {
+            assert_eq!(
+                DistanceBoard::with_grass_forecast(&board, owner, &mine_duration_board).distances,
+                DistanceBoard::from_owner(&board, owner).distances,
+            );
+        }
+    }
+
+    #[test]
+    fn forecast_skips_fields_that_are_grass_on_arrival() {
+        // the recycler mines out the 1 after one harvest, and the 2s after two when it runs out itself
+        let board = Board::from_ascii("
+            5m1 5 2   5
+            5   1 2oR 5
+            5   5 5   5
+        ").unwrap();
+        let frozen = DistanceBoard::from_owner(&board, Owner::Me);
+        let forecast = DistanceBoard::with_grass_forecast(&board, Owner::Me, &MineDurationBoard::new(&board));
+
+        assert_eq!(frozen.get_field(3, 1), Some(&ManhattanDistance::Dist(4)));
+        // both are 2 moves away: (1, 1) is grass a turn before a robot gets there,
+        // and (2, 0) turns to grass in the harvest of the turn the robot arrives
+        assert_eq!(forecast.get_field(1, 1), Some(&ManhattanDistance::Unreachable));
+        assert_eq!(forecast.get_field(2, 0), Some(&ManhattanDistance::Unreachable));
+        assert_eq!(forecast.get_field(3, 1), Some(&ManhattanDistance::Dist(6)));
+
+        let towards = DistanceBoard::through(&board, Owner::Neutral, &forecast);
+        assert_eq!(towards.get_field(2, 0), Some(&ManhattanDistance::Unreachable));
+    }
+
+    #[test]
+    fn forecast_from_a_field_counts_from_the_departure() {
+        let board = Board::from_ascii("
+            5m1 5 2   5
+            5   1 2oR 5
+            5   5 5   5
+        ").unwrap();
+        let mine_duration_board = MineDurationBoard::new(&board);
+        let mut search = DistanceSearch::default();
+
+        let now = search.from_coord_with_grass_forecast(&board, Coord::new(1, 0), 0, &mine_duration_board);
+        let next_turn = search.from_coord_with_grass_forecast(&board, Coord::new(1, 0), 1, &mine_duration_board);
+        assert_eq!(now.get_field(2, 0), Some(&ManhattanDistance::Dist(1)));
+        // a turn later (2, 0) is mined out by the time the robots get there
+        assert_eq!(next_turn.get_field(1, 0), Some(&ManhattanDistance::Dist(1)));
+        assert_eq!(next_turn.get_field(2, 0), Some(&ManhattanDistance::Unreachable));
+    }
+
+    #[test]
+    fn finds_all_shortest_paths() {
+        let board = Board::from_ascii("
+            5m1 5 5
+            5   5 5
+            5   . 5
+        ").unwrap();
+        let distances = DistanceBoard::from_owner(&board, Owner::Me);
+        let corner = Coord::new(2, 2);
+
+        assert_eq!(distances.next_steps(corner), vec![Coord::new(2, 1)]);
+        assert_eq!(distances.next_steps(Coord::new(1, 1)), vec![Coord::new(1, 0), Coord::new(0, 1)]);
+        let path = distances.path_to_source(corner).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!((path[0], path[4]), (corner, Coord::new(0, 0)));
+
+        // three ways from the corner to the robot, all through (2, 1)
+        let counts = distances.shortest_path_counts(corner);
+        assert_eq!(counts[Coord::new(2, 1)], 3);
+        assert_eq!(counts[Coord::new(1, 1)], 2);
+        assert_eq!(counts[Coord::new(2, 0)], 1);
+        assert_eq!(counts[Coord::new(0, 0)], 3);
+        assert_eq!(counts[Coord::new(0, 2)], 0);
+    }
+
+    #[test]
+    fn a_star_avoids_expensive_owners() {
+        let board = Board::from_ascii("
+            5m1 5o 5o 5
+            5   5  5  5
+        ").unwrap();
+        let (from, to) = (Coord::new(0, 0), Coord::new(3, 0));
+
+        assert_eq!(DistanceBoard::a_star(&board, from, to, &PathWeights::default()).unwrap().0, 3);
+        let weights = PathWeights { opponent: 5, ..Default::default() };
+        let (cost, path) = DistanceBoard::a_star(&board, from, to, &weights).unwrap();
+        assert_eq!(cost, 5);
+        assert_eq!(path, vec![from, Coord::new(0, 1), Coord::new(1, 1), Coord::new(2, 1), Coord::new(3, 1), to]);
+        assert_eq!(DistanceBoard::a_star(&board, from, Coord::new(1, 0), &weights), Some((5, vec![from, Coord::new(1, 0)])));
+    }
+}
@@ -1,4 +1,5 @@
-use super::Owner;
+use super::{Coord, Owner};
+use super::boardbuilder::{parse_value, InputError};
 
 
 #[derive(Copy, Clone, Debug, Default)]
@@ -23,25 +24,44 @@ impl Field {
         !self.is_grass() && !self.has_recycler
     }
 
-    pub fn from_input_line(input_line: &[&str], x: u32, y: u32) -> Self {
-        let scrap_amount = parse_input!(input_line[0], i32);
-        let owner = parse_input!(input_line[1], i32); // 1 = me, 0 = foe, -1 = neutral
-        let units = parse_input!(input_line[2], i32);
-        let recycler = parse_input!(input_line[3], i32);
-        let can_build = parse_input!(input_line[4], i32);
-        let can_spawn = parse_input!(input_line[5], i32);
-        let in_range_of_recycler = parse_input!(input_line[6], i32);
+    pub fn coord(&self) -> Coord {
+        Coord::new(self.x, self.y)
+    }
+
+    /// Errors are reported on line 0, the caller knows which line this is
+    pub fn from_input_line(input_line: &[&str], x: u32, y: u32) -> Result<Self, InputError> {
+        let scrap_amount = parse_value::<u32>(input_line, 0)?;
+        let owner = parse_value::<i32>(input_line, 1)?; // 1 = me, 0 = foe, -1 = neutral
+        let units = parse_value::<u32>(input_line, 2)?;
+        let recycler = parse_value::<i32>(input_line, 3)?;
+        let can_build = parse_value::<i32>(input_line, 4)?;
+        let can_spawn = parse_value::<i32>(input_line, 5)?;
+        let in_range_of_recycler = parse_value::<i32>(input_line, 6)?;
 
-        Self {
+        Ok(Self {
             x,
             y,
-            scrap_amount: scrap_amount as u32,
-            owner: Owner::from_num(owner),
-            num_units: units as u32,
+            scrap_amount,
+            owner: Owner::from_num(owner).ok_or_else(|| InputError::invalid(2, format!("unknown owner {}", owner)))?,
+            num_units: units,
             has_recycler: recycler == 1,
             can_build: can_build == 1,
             can_spawn: can_spawn == 1,
             in_recycler_range: in_range_of_recycler == 1,
-        }
+        })
+    }
+
+    /// Formats the field the same way the referee sends it
+    pub fn to_input_line(&self) -> String {
+        format!(
+            "{} {} {} {} {} {} {}",
+            self.scrap_amount,
+            self.owner.to_num(),
+            self.num_units,
+            self.has_recycler as u8,
+            self.can_build as u8,
+            self.can_spawn as u8,
+            self.in_recycler_range as u8,
+        )
     }
 }
@@ -0,0 +1,199 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+use std::slice;
+use super::{Board, Coord, Field};
+
+/// A value for every field of the board, stored row by row
+///
+/// This is what all the analysis boards are built on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: u32,
+    height: u32,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+
+    /// `cells` are given row by row, and there must be exactly `width * height` of them
+    pub fn new(width: u32, height: u32, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), (width * height) as usize, "grid size doesn't match the number of cells");
+        Self { width, height, cells }
+    }
+
+    pub fn filled(width: u32, height: u32, value: T) -> Self where T: Clone {
+        Self::new(width, height, vec![value; (width * height) as usize])
+    }
+
+    pub fn from_fn(width: u32, height: u32, mut f: impl FnMut(Coord) -> T) -> Self {
+        let cells = (0..(width * height) as usize)
+            .map(|i| f(Coord::from_index(i, width)))
+            .collect();
+        Self::new(width, height, cells)
+    }
+
+    /// A value for every field on the board
+    pub fn from_board(board: &Board, f: impl FnMut(&Field) -> T) -> Self {
+        Self::new(board.width, board.height, board.fields.iter().map(f).collect())
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        if coord.in_bounds(self.width, self.height) { self.cells.get(coord.index(self.width)) } else { None }
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        if coord.in_bounds(self.width, self.height) { self.cells.get_mut(coord.index(self.width)) } else { None }
+    }
+
+    /// The values of the neighbours in NESW order, `None` where that side is off the board
+    pub fn adjacent(&self, coord: Coord) -> [Option<&T>; 4] {
+        coord
+            .adjacent(self.width, self.height)
+            .map(|x| x.and_then(|c| self.get(c)))
+    }
+
+    /// The neighbours on the board with their values, in NESW order
+    pub fn neighbours(&self, coord: Coord) -> impl Iterator<Item = (Coord, &T)> {
+        coord
+            .neighbours(self.width, self.height)
+            .map(move |c| (c, &self[c]))
+    }
+
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| Coord::from_index(i, width))
+    }
+
+    /// All values, row by row
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    /// All values with their coord, row by row
+    pub fn enumerate(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.coords().zip(self.cells.iter())
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    /// Combines two grids of the same size field by field
+    pub fn zip_with<U, V>(&self, other: &Grid<U>, mut f: impl FnMut(&T, &U) -> V) -> Grid<V> {
+        assert!(self.width == other.width && self.height == other.height, "grids differ in size");
+        let cells = self.cells
+            .iter()
+            .zip(other.cells.iter())
+            .map(|(a, b)| f(a, b))
+            .collect();
+        Grid::new(self.width, self.height, cells)
+    }
+
+    pub fn row(&self, y: u32) -> &[T] {
+        let start = (y * self.width) as usize;
+        &self.cells[start..start + self.width as usize]
+    }
+
+    pub fn rows(&self) -> slice::Chunks<'_, T> {
+        self.cells.chunks(self.width.max(1) as usize)
+    }
+
+    pub fn column(&self, x: u32) -> impl Iterator<Item = &T> {
+        self.cells
+            .iter()
+            .skip(x as usize)
+            .step_by(self.width.max(1) as usize)
+            .take(if x < self.width { self.height as usize } else { 0 })
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.cells
+    }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &Self::Output {
+        self.get(coord).expect("coord is off the grid")
+    }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, coord: Coord) -> &mut Self::Output {
+        self.get_mut(coord).expect("coord is off the grid")
+    }
+}
+
+/// Prints the grid row by row, with the columns aligned
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let cells = self.cells.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        let cell_width = cells.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+
+        for row in cells.chunks(self.width.max(1) as usize) {
+            let row = row
+                .iter()
+                .map(|x| format!("{:>width$}", x, width = cell_width))
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_and_columns_follow_the_layout() {
+        let grid = Grid::from_fn(3, 2, |c| c.x + 10 * c.y);
+
+        assert_eq!(grid.row(1), &[10, 11, 12]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), vec![2, 12]);
+        assert_eq!(grid.columns().count(), 3);
+        assert_eq!(grid[Coord::new(1, 1)], 11);
+        assert!(grid.get(Coord::new(3, 0)).is_none());
+    }
+
+    #[test]
+    fn neighbours_stay_on_the_grid() {
+        let grid = Grid::from_fn(3, 2, |c| c.x + 10 * c.y);
+        let neighbours = grid.neighbours(Coord::new(0, 1)).map(|(_, x)| *x).collect::<Vec<_>>();
+
+        assert_eq!(neighbours, vec![0, 11]);
+    }
+
+    #[test]
+    fn display_aligns_columns() {
+        let grid = Grid::new(2, 2, vec![1, 20, 300, 4]);
+
+        assert_eq!(grid.to_string(), "  1  20\n300   4\n");
+    }
+}
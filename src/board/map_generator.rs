@@ -0,0 +1,196 @@
+use std::collections::VecDeque;
+use super::super::rng::Rng;
+use super::{Board, Coord, Field, Owner};
+
+pub const MIN_HEIGHT: u32 = 6;
+pub const MAX_HEIGHT: u32 = 12;
+pub const MAX_SCRAP: u32 = 10;
+pub const STARTING_MATTER: u32 = 10;
+
+/// Generates maps following the rules of the arena:
+/// point-symmetric, twice as wide as high, random scrap amounts with patches of grass,
+/// and a 3x3 starting area per player with robots next to its center.
+///
+/// The same seed always gives the same map.
+#[derive(Copy, Clone, Debug)]
+pub struct MapGenerator {
+    pub seed: u64,
+    /// Guarantees every non-grass field can be reached from both starting areas
+    pub connected: bool,
+}
+
+impl MapGenerator {
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            connected: true,
+        }
+    }
+
+    pub fn generate(&self) -> Board {
+        let mut rng = Rng::new(self.seed);
+
+        loop {
+            if let Some(board) = self.try_generate(&mut rng) {
+                return board;
+            }
+        }
+    }
+
+    /// Returns `None` when the starting areas ended up disconnected on a map that needs to be connected
+    fn try_generate(&self, rng: &mut Rng) -> Option<Board> {
+        let height = rng.range(MIN_HEIGHT, MAX_HEIGHT);
+        let width = 2 * height;
+
+        let scrap = (0..width * height)
+            .map(|_| rng.range(1, MAX_SCRAP))
+            .collect::<Vec<_>>();
+
+        // smooth the scrap amounts, so richer and poorer areas form
+        let mut scrap = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let adjacent = Coord::new(x, y).neighbours(width, height).collect::<Vec<_>>();
+                let total = scrap[(x + y * width) as usize] + adjacent.iter().map(|a| scrap[a.index(width)]).sum::<u32>();
+                (total + adjacent.len() as u32 / 2) / (adjacent.len() as u32 + 1)
+            })
+            .collect::<Vec<_>>();
+
+        // grass patches
+        let patch_count = rng.range(0, height);
+        for _ in 0..patch_count {
+            let (center_x, center_y) = (rng.range(0, width - 1), rng.range(0, height - 1));
+            let radius = rng.range(0, 2);
+            for y in center_y.saturating_sub(radius)..=(center_y + radius).min(height - 1) {
+                for x in center_x.saturating_sub(radius)..=(center_x + radius).min(width - 1) {
+                    if x.abs_diff(center_x) + y.abs_diff(center_y) <= radius {
+                        scrap[(x + y * width) as usize] = 0;
+                    }
+                }
+            }
+        }
+
+        // mirror the first half onto the second half, so the map is point-symmetric
+        let length = scrap.len();
+        for i in 0..length / 2 {
+            scrap[length - 1 - i] = scrap[i];
+        }
+
+        let mut board = Board {
+            width,
+            height,
+            my_matter: STARTING_MATTER,
+            opponent_matter: STARTING_MATTER,
+            fields: scrap
+                .iter()
+                .enumerate()
+                .map(|(i, scrap_amount)| Field {
+                    x: i as u32 % width,
+                    y: i as u32 / width,
+                    scrap_amount: *scrap_amount,
+                    ..Default::default()
+                })
+                .collect(),
+        };
+
+        let my_center = (rng.range(1, width / 4), rng.range(1, height - 2));
+        let opponent_center = (width - 1 - my_center.0, height - 1 - my_center.1);
+        for (owner, (center_x, center_y)) in [(Owner::Me, my_center), (Owner::Opponent, opponent_center)] {
+            for y in center_y - 1..=center_y + 1 {
+                for x in center_x - 1..=center_x + 1 {
+                    let field = board.get_field_mut(x, y).unwrap();
+                    field.owner = owner;
+                    field.scrap_amount = field.scrap_amount.max(1);
+                    // robots start on the fields next to the center
+                    field.num_units = if (x == center_x) != (y == center_y) { 1 } else { 0 };
+                }
+            }
+        }
+
+        if self.connected {
+            let reachable = reachable_from(&board, my_center);
+            if !reachable[(opponent_center.0 + opponent_center.1 * width) as usize] {
+                return None;
+            }
+
+            // Islands nobody can reach become grass, this keeps the map symmetric
+            for (field, reachable) in board.fields.iter_mut().zip(reachable) {
+                if !reachable {
+                    field.scrap_amount = 0;
+                }
+            }
+        }
+
+        board.update_build_spawn_flags();
+        Some(board)
+    }
+}
+
+/// Flood fill over all non-grass fields
+fn reachable_from(board: &Board, start: (u32, u32)) -> Vec<bool> {
+    let start = Coord::from(start);
+    let mut reachable = vec![false; board.fields.len()];
+    let mut frontier = VecDeque::from([start]);
+    reachable[start.index(board.width)] = true;
+
+    while let Some(current) = frontier.pop_front() {
+        for neighbour in current.neighbours(board.width, board.height) {
+            let index = neighbour.index(board.width);
+            if !reachable[index] && !board.fields[index].is_grass() {
+                reachable[index] = true;
+                frontier.push_back(neighbour);
+            }
+        }
+    }
+
+    reachable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_map() {
+        for seed in 0..20 {
+            assert_eq!(MapGenerator::new(seed).generate().to_ascii(), MapGenerator::new(seed).generate().to_ascii());
+        }
+        assert_ne!(MapGenerator::new(1).generate().to_ascii(), MapGenerator::new(2).generate().to_ascii());
+    }
+
+    #[test]
+    fn maps_are_point_symmetric_with_one_start_area_each() {
+        for seed in 0..50 {
+            let board = MapGenerator::new(seed).generate();
+            assert_eq!(board.width, 2 * board.height);
+
+            for (field, mirrored) in board.fields.iter().zip(board.fields.iter().rev()) {
+                assert_eq!(field.scrap_amount, mirrored.scrap_amount);
+                assert_eq!(field.num_units, mirrored.num_units);
+                assert_eq!(field.owner, mirrored.owner.swapped());
+            }
+
+            let start_area = board.fields.iter().filter(|x| x.owner == Owner::Me).collect::<Vec<_>>();
+            let (min_x, max_x) = (start_area.iter().map(|x| x.x).min().unwrap(), start_area.iter().map(|x| x.x).max().unwrap());
+            let (min_y, max_y) = (start_area.iter().map(|x| x.y).min().unwrap(), start_area.iter().map(|x| x.y).max().unwrap());
+            assert_eq!((start_area.len(), max_x - min_x, max_y - min_y), (9, 2, 2));
+            assert!(start_area.iter().all(|x| !x.is_grass()));
+            assert_eq!(board.robot_count(Owner::Me), 4);
+            assert_eq!(board.get_field(min_x + 1, min_y + 1).unwrap().num_units, 0);
+        }
+    }
+
+    #[test]
+    fn connected_maps_reach_every_field() {
+        for seed in 0..50 {
+            let board = MapGenerator::new(seed).generate();
+            let start = board.fields.iter().find(|x| x.owner == Owner::Me).unwrap();
+            let reachable = reachable_from(&board, (start.x, start.y));
+
+            for (field, reachable) in board.fields.iter().zip(reachable) {
+                assert_eq!(reachable, !field.is_grass(), "seed {} at ({}, {})", seed, field.x, field.y);
+            }
+        }
+    }
+}
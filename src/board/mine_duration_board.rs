@@ -1,12 +1,15 @@
-use std::iter::zip;
-use super::Board;
+use std::fmt::{Display, Formatter};
+use super::{Board, Coord, Grid};
 
-/// Contains how many turns until each field is mined empty
+/// Contains how many turns until each field is mined empty, assuming no new recyclers are built
+///
+/// Durations count harvest cycles from the given board:
+/// `Duration(1)` means the field is grass after the harvest at the end of this turn.
 #[derive(Clone, Debug)]
 pub struct MineDurationBoard {
-    width: u32,
-    height: u32,
-    mine_durations: Vec<MineDuration>,
+    pub mine_durations: Grid<MineDuration>,
+    /// For every field with a recycler, the number of harvest cycles before it runs out
+    pub recycler_durations: Grid<Option<u32>>,
 }
 
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
@@ -15,51 +18,110 @@ pub enum MineDuration {
     Unending,
 }
 
-impl MineDurationBoard {
-
-    pub fn new(board: &Board) -> Self {
-        todo!();
-        // TODO: maybe deprecate => recyclers can be depleted faster than
-        // the scrap amount of the field they're on
-
-        let mut mine_durations = vec![MineDuration::Unending; board.fields.len()];
-
-        for (mut dur, field) in zip(mine_durations, board.fields.iter()) {
-            let recycler_scrap_counts = board.get_adjacent_fields(field.x, field.y)
-                .into_iter()
-                .flatten()
-                .filter(|x| x.has_recycler)
-                .map(|x| x.scrap_amount)
-                .collect::<Vec<_>>();
-
-
+impl MineDuration {
+    /// Whether the field still has scrap after `turns` harvest cycles
+    pub fn survives(self, turns: u32) -> bool {
+        match self {
+            MineDuration::Duration(x) => x > turns,
+            MineDuration::Unending => true,
+        }
+    }
+}
 
-            let mut scrap_left = field.scrap_amount;
-            let mut turns_mined = 0_u32;
+impl Display for MineDuration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MineDuration::Duration(x) => write!(f, "{}", x),
+            MineDuration::Unending => write!(f, "-"),
+        }
+    }
+}
 
-            while scrap_left > 0 {
+impl MineDurationBoard {
 
+    pub fn new(board: &Board) -> Self {
+        // Recyclers can be depleted faster than the scrap amount of the field they're on suggests,
+        // and fields in range of several recyclers still only lose 1 scrap per turn.
+        // So simply play the harvest cycles out until all recyclers are gone.
+        let mut mine_durations = Grid::from_board(board, |x| {
+            if x.is_grass() { MineDuration::Duration(0) } else { MineDuration::Unending }
+        });
+        let mut recycler_durations = Grid::filled(board.width, board.height, None);
+
+        let mut current = board.clone();
+        let mut turn = 0;
+        while current.fields.iter().any(|x| x.has_recycler) {
+            let next = current.process_harvest_cycle();
+            turn += 1;
+
+            for (before, after) in current.fields.iter().zip(next.fields.iter()) {
+                if !before.is_grass() && after.is_grass() {
+                    mine_durations[before.coord()] = MineDuration::Duration(turn);
+                }
+                if before.has_recycler && !after.has_recycler {
+                    recycler_durations[before.coord()] = Some(turn);
+                }
             }
 
-            // if recycler_fields.len() > 0 {
-            //     let mut scrap_left = field.scrap_amount;
-            //     let mut turns_mined = 0_u32;
-            //
-            //
-            // }
-
+            current = next;
         }
 
-
-        // TODO: remove
-        let mine_durations = Vec::new();
-
         Self {
-            width: board.width,
-            height: board.height,
             mine_durations,
+            recycler_durations,
         }
     }
 
+    pub fn get_field(&self, x: u32, y: u32) -> Option<&MineDuration> {
+        self.mine_durations.get(Coord::new(x, y))
+    }
+
+    pub fn get_recycler(&self, x: u32, y: u32) -> Option<u32> {
+        self.recycler_durations.get(Coord::new(x, y)).copied().flatten()
+    }
+
+    /// Whether a robot arriving on the field in `turns` turns will survive the harvest of that turn
+    pub fn is_safe_after(&self, x: u32, y: u32, turns: u32) -> bool {
+        self.get_field(x, y).is_some_and(|d| d.survives(turns))
+    }
+
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_fields_lose_one_scrap_per_turn() {
+        let board = Board::from_ascii("
+            5mR 3 4mR
+        ").unwrap();
+        let mine_duration_board = MineDurationBoard::new(&board);
+
+        // the middle field is in range of both recyclers, but still lasts 3 turns
+        assert_eq!(mine_duration_board.mine_durations.iter().copied().collect::<Vec<_>>(), vec![
+            MineDuration::Duration(5), MineDuration::Duration(3), MineDuration::Duration(4),
+        ]);
+        assert_eq!(mine_duration_board.get_recycler(0, 0), Some(5));
+        assert_eq!(mine_duration_board.get_recycler(2, 0), Some(4));
+        assert!(mine_duration_board.is_safe_after(1, 0, 2));
+        assert!(!mine_duration_board.is_safe_after(1, 0, 3));
+    }
+
+    #[test]
+    fn fields_outlast_a_recycler_that_runs_out_first() {
+        let board = Board::from_ascii("
+            9 2mR 1
+            9 9   9
+        ").unwrap();
+        let mine_duration_board = MineDurationBoard::new(&board);
+
+        assert_eq!(mine_duration_board.get_recycler(1, 0), Some(2));
+        assert_eq!(mine_duration_board.get_field(1, 0), Some(&MineDuration::Duration(2)));
+        assert_eq!(mine_duration_board.get_field(2, 0), Some(&MineDuration::Duration(1)));
+        // harvested twice, then the recycler is gone
+        assert_eq!(mine_duration_board.get_field(0, 0), Some(&MineDuration::Unending));
+        assert_eq!(mine_duration_board.get_field(1, 1), Some(&MineDuration::Unending));
+        assert!(mine_duration_board.is_safe_after(0, 0, 100));
+    }
+}
@@ -1,15 +1,26 @@
 pub mod owner;
+pub mod coord;
+pub mod grid;
 pub mod boardbuilder;
 pub mod field;
 pub mod distance_board;
 pub mod yield_board;
 pub mod mine_duration_board;
 pub mod recycler_range_board;
+pub mod territory_board;
+pub mod region_board;
+pub mod chokepoint_board;
+pub mod simulation;
+pub mod map_generator;
+pub mod ascii;
+pub mod render;
 
-use std::cmp::{max, min};
 pub use owner::*;
 pub use field::*;
+pub use coord::*;
+pub use grid::Grid;
 
+#[derive(Clone, Debug)]
 pub struct Board {
     pub width: u32,
     pub height: u32,
@@ -21,30 +32,24 @@ pub struct Board {
 impl Board {
 
     pub fn get_field(&self, width: u32, height: u32) -> Option<&Field> {
-        self.fields.get((width + height * self.width) as usize)
+        let coord = Coord::new(width, height);
+        if coord.in_bounds(self.width, self.height) { self.fields.get(coord.index(self.width)) } else { None }
     }
 
     pub fn get_field_mut(&mut self, width: u32, height: u32) -> Option<&mut Field> {
-        self.fields.get_mut((width + height * self.width) as usize)
+        let coord = Coord::new(width, height);
+        if coord.in_bounds(self.width, self.height) { self.fields.get_mut(coord.index(self.width)) } else { None }
     }
 
     pub fn get_adjacent_fields(&self, width: u32, height: u32) -> [Option<&Field>; 4] { // NESW
-        [
-            self.get_field(width, height - 1),
-            self.get_field(width + 1, height),
-            self.get_field(width, height + 1),
-            self.get_field(width - 1, height),
-        ]
+        Coord::new(width, height)
+            .adjacent(self.width, self.height)
+            .map(|x| x.and_then(|c| self.get_field(c.x, c.y)))
     }
 
     pub fn get_fields_in_range(&self, width: u32, height: u32) -> [Option<&Field>; 5] { // cur + NESW
-        [
-            self.get_field(width, height),
-            self.get_field(width, height - 1),
-            self.get_field(width + 1, height),
-            self.get_field(width, height + 1),
-            self.get_field(width - 1, height),
-        ]
+        let [n, e, s, w] = self.get_adjacent_fields(width, height);
+        [self.get_field(width, height), n, e, s, w]
     }
 
     pub fn robot_count(&self, owner: Owner) -> u32 {
@@ -58,6 +63,14 @@ impl Board {
             .sum()
     }
 
+    /// Number of fields owned, which is what decides the game
+    pub fn territory_count(&self, owner: Owner) -> u32 {
+        self.fields
+            .iter()
+            .filter(|x| x.owner == owner && !x.is_grass())
+            .count() as u32
+    }
+
     pub fn adjacent_robot_count(&self, x: u32, y: u32, owner: Owner) -> u32 {
         let adj = self.get_adjacent_fields(x, y);
         let adj = adj
@@ -70,73 +83,200 @@ impl Board {
             .sum()
     }
 
+    /// Runs one harvest step of all recyclers on the board.
+    ///
+    /// A tile loses at most 1 scrap per turn, even when it is in range of multiple recyclers.
+    /// Every player with a recycler in range of a tile still gets 1 matter from it.
     pub fn process_harvest_cycle(&self) -> Self {
-        let new_fields = self.fields.clone();
+        let mut new_board = self.clone();
 
-        let mut new_board = Board {
-            fields: new_fields,
-            ..*self
-        };
-
-        // reduce scrap per tile amount by min(#surrounding recyclers in range, scrap)
         for i in 0..new_board.width {
             for j in 0..new_board.height {
-                let mut fields_in_range = new_board.get_fields_in_range(i, j);
-
-                let mut num_recyclers_in_range = fields_in_range
+                let fields_in_range = self.get_fields_in_range(i, j);
+                let recycler_owners = fields_in_range
                     .into_iter()
                     .flatten()
-                    .filter(|x| {x.has_recycler})
-                    .count();
+                    .filter(|x| x.has_recycler)
+                    .map(|x| x.owner);
 
-                let mut current_field = new_board.get_field_mut(i, j).unwrap();
-                // Prevents unsigned int underflow
-                current_field.scrap_amount -= min(current_field.scrap_amount, num_recyclers_in_range as u32);
+                let (mut mine, mut opponent) = (false, false);
+                for owner in recycler_owners {
+                    mine |= owner == Owner::Me;
+                    opponent |= owner == Owner::Opponent;
+                }
+
+                let current_field = new_board.get_field_mut(i, j).unwrap();
+                if current_field.scrap_amount == 0 || !(mine || opponent) {
+                    continue;
+                }
+                current_field.scrap_amount -= 1;
+
+                if mine {
+                    new_board.my_matter += 1;
+                }
+                if opponent {
+                    new_board.opponent_matter += 1;
+                }
             }
         }
 
         // Clean up recyclers
-        new_board.fields = new_board.fields
-            .into_iter()
-            .map(|mut x| {
-                x.has_recycler = x.has_recycler && x.scrap_amount != 0;
-                x
-            })
-            .collect();
+        for field in new_board.fields.iter_mut() {
+            field.has_recycler = field.has_recycler && field.scrap_amount != 0;
+        }
 
-        // Update whether fields are in recycler range
-        for i in 0..new_board.width {
-            for j in 0..new_board.height {
-                let mut fields_in_range = new_board.get_fields_in_range(i, j);
+        new_board.update_recycler_range();
+        new_board
+    }
+
+    /// Update whether fields are in recycler range
+    pub fn update_recycler_range(&mut self) {
+        for i in 0..self.width {
+            for j in 0..self.height {
+                let fields_in_range = self.get_fields_in_range(i, j);
                 let in_recycler_range = fields_in_range
                         .into_iter()
                         .flatten()
                         .any(|x| x.has_recycler);
 
-                new_board.get_field_mut(i, j).unwrap().in_recycler_range = in_recycler_range;
+                self.get_field_mut(i, j).unwrap().in_recycler_range = in_recycler_range;
             }
         }
-
-        new_board
     }
 
     /// Returns what the board would like if all current recyclers were mined out
     pub fn mined_out(&self) -> Self {
-        todo!()
-
         // Note: recyclers will only mine for as long as they have scrap directly underneath them
         // Tile can be in mining range of multiple recyclers
+        let mut board = self.clone();
+        while board.fields.iter().any(|x| x.has_recycler) {
+            board = board.process_harvest_cycle();
+        }
+
+        board.clear_grass();
+        board
+    }
+
+    /// Returns the board as the opponent sees it
+    pub fn swap_perspective(&self) -> Self {
+        let mut board = Board {
+            my_matter: self.opponent_matter,
+            opponent_matter: self.my_matter,
+            ..self.clone()
+        };
+
+        for field in board.fields.iter_mut() {
+            field.owner = field.owner.swapped();
+        }
+        board.update_build_spawn_flags();
+        board
     }
-}
 
-pub fn adjacent_in_range(x: u32, y: u32, width: u32, height: u32) -> [Option<(u32, u32)>; 4] {
-    // NESW
-    [
-        if 0 <= x && x < width && 0 <= y-1 && y-1 < height { Some((x, y-1)) } else { None },
-        if 0 <= x+1 && x+1 < width && 0 <= y && y < height { Some((x+1, y)) } else { None },
-        if 0 <= x && x < width && 0 <= y+1 && y+1 < height { Some((x, y+1)) } else { None },
-        if 0 <= x-1 && x-1 < width && 0 <= y && y < height { Some((x-1, y)) } else { None },
-    ]
+    /// Formats the turn input the same way the referee sends it: matter first, then every field
+    pub fn to_input(&self) -> String {
+        let mut result = format!("{} {}\n", self.my_matter, self.opponent_matter);
+        for field in self.fields.iter() {
+            result.push_str(&field.to_input_line());
+            result.push('\n');
+        }
+
+        result
+    }
+
+    /// Robots and recyclers on grass are removed
+    pub fn clear_grass(&mut self) {
+        for field in self.fields.iter_mut().filter(|f| f.is_grass()) {
+            field.num_units = 0;
+            field.has_recycler = false;
+            field.owner = Owner::Neutral;
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::boardbuilder::BoardBuilder;
+
+    fn sample() -> Board {
+        let board = Board::from_ascii("
+            5m2 5mR 3  .
+            7   5o  4oR 6o1
+        ").unwrap();
+        Board { my_matter: 12, opponent_matter: 30, ..board }
+    }
+
+    #[test]
+    fn the_referee_input_reads_back_as_the_same_board() {
+        let board = sample();
+        let input = board.to_input();
+        assert_eq!(input.lines().take(3).collect::<Vec<_>>(), vec!["12 30", "5 1 2 0 0 1 1", "5 1 0 1 0 0 1"]);
+
+        let read = BoardBuilder::new(board.width, board.height)
+            .fields_from_reader(&mut input.as_bytes())
+            .unwrap()
+            .build();
+        assert_eq!(read.to_input(), input);
+        assert!(read.fields.iter().enumerate().all(|(i, f)| f.coord() == Coord::from_index(i, board.width)));
+    }
 
+    #[test]
+    fn swapping_the_perspective_swaps_owners_and_matter() {
+        let board = sample();
+        let swapped = board.swap_perspective();
+
+        assert_eq!((swapped.my_matter, swapped.opponent_matter), (30, 12));
+        assert_eq!(swapped.territory_count(Owner::Me), board.territory_count(Owner::Opponent));
+        assert_eq!((swapped.fields[0].owner, swapped.fields[5].owner, swapped.fields[2].owner), (Owner::Opponent, Owner::Me, Owner::Neutral));
+        // what we can do with a field depends on whose it is
+        assert!(!swapped.fields[0].can_spawn && swapped.fields[5].can_build && swapped.fields[7].can_spawn);
+
+        assert_eq!(swapped.swap_perspective().to_input(), board.to_input());
+    }
+
+    #[test]
+    fn territory_only_counts_fields_that_are_not_grass() {
+        let mut board = sample();
+        assert_eq!((board.territory_count(Owner::Me), board.territory_count(Owner::Opponent), board.territory_count(Owner::Neutral)), (2, 3, 2));
+
+        // a field that just turned to grass still has its owner until the grass is cleared
+        board.fields[1].scrap_amount = 0;
+        assert_eq!(board.territory_count(Owner::Me), 1);
+    }
+
+    #[test]
+    fn adjacent_robot_count_stays_on_the_board() {
+        let board = Board::from_ascii("
+            1   1 1o3
+            1m5 1 1
+        ").unwrap();
+
+        assert_eq!(board.adjacent_robot_count(2, 0, Owner::Me), 0);
+        assert_eq!(board.adjacent_robot_count(0, 1, Owner::Opponent), 0);
+        assert_eq!(board.adjacent_robot_count(1, 0, Owner::Opponent), 3);
+        assert_eq!(board.adjacent_robot_count(0, 0, Owner::Me), 5);
+    }
+
+    #[test]
+    fn fields_outside_the_board_do_not_exist() {
+        let board = Board::from_ascii("1 2\n3 4").unwrap();
+
+        assert!(board.get_field(2, 0).is_none());
+        assert!(board.get_field(0, 2).is_none());
+        assert_eq!(board.get_adjacent_fields(1, 1).iter().flatten().count(), 2);
+        assert_eq!(board.get_fields_in_range(0, 0).iter().flatten().count(), 3);
+    }
+
+    #[test]
+    fn mined_out_runs_the_recyclers_dry() {
+        let board = Board::from_ascii("
+            9 2mR 1m1 5
+        ").unwrap();
+        let mined_out = board.mined_out();
+
+        assert_eq!(mined_out.fields.iter().map(|x| x.scrap_amount).collect::<Vec<_>>(), vec![7, 0, 0, 5]);
+        assert!(mined_out.fields.iter().all(|x| !x.has_recycler));
+        // grass loses its owner and robots
+        assert_eq!((mined_out.fields[2].owner, mined_out.fields[2].num_units), (Owner::Neutral, 0));
+    }
+}
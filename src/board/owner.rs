@@ -1,27 +1,39 @@
 use std::cmp::Ordering;
 
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 pub enum Owner {
     Me,
     Opponent,
+    #[default]
     Neutral,
 }
 
 impl Owner {
-    pub fn from_num(num: i32) -> Self {
+    pub fn from_num(num: i32) -> Option<Self> {
         match num {
-            -1 => Owner::Neutral,
-            0 => Owner::Opponent,
-            1 => Owner::Me,
-            _ => panic!("unknown owner num"),
+            -1 => Some(Owner::Neutral),
+            0 => Some(Owner::Opponent),
+            1 => Some(Owner::Me),
+            _ => None,
         }
     }
-}
 
-impl Default for Owner {
-    fn default() -> Self {
-        Owner::Neutral
+    pub fn to_num(self) -> i32 {
+        match self {
+            Owner::Neutral => -1,
+            Owner::Opponent => 0,
+            Owner::Me => 1,
+        }
+    }
+
+    /// The same owner, seen from the other player's side of the board
+    pub fn swapped(self) -> Self {
+        match self {
+            Owner::Me => Owner::Opponent,
+            Owner::Opponent => Owner::Me,
+            Owner::Neutral => Owner::Neutral,
+        }
     }
 }
 
@@ -29,12 +41,10 @@ impl Eq for Owner {}
 
 impl PartialEq<Self> for Owner {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Owner::Me, Owner::Me) => true,
-            (Owner::Neutral, Owner::Neutral) => true,
-            (Owner::Opponent, Owner::Opponent) => true,
-            _ => false,
-        }
+        matches!(
+            (self, other),
+            (Owner::Me, Owner::Me) | (Owner::Neutral, Owner::Neutral) | (Owner::Opponent, Owner::Opponent)
+        )
     }
 }
 
@@ -0,0 +1,183 @@
+use super::{Board, Coord, Grid, Owner};
+
+/// Splits the traversible fields into islands: fields robots can walk between
+///
+/// Grass and recyclers separate the islands, so nothing can move from one island to another.
+#[derive(Clone, Debug)]
+pub struct RegionBoard {
+    /// The index into `islands` of every traversible field
+    pub regions: Grid<Option<usize>>,
+    pub islands: Vec<Island>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Island {
+    pub size: u32,
+    pub my_fields: u32,
+    pub opponent_fields: u32,
+    pub neutral_fields: u32,
+    pub my_units: u32,
+    pub opponent_units: u32,
+    pub scrap: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IslandStatus {
+    /// Both players have fields on the island
+    Contested,
+    /// Only this player has fields on the island, the other can't get in anymore
+    Decided(Owner),
+    /// Nobody has fields on the island, so nobody can get in
+    Unclaimed,
+}
+
+impl Island {
+
+    pub fn status(&self) -> IslandStatus {
+        match (self.my_fields > 0, self.opponent_fields > 0) {
+            (true, true) => IslandStatus::Contested,
+            (true, false) => IslandStatus::Decided(Owner::Me),
+            (false, true) => IslandStatus::Decided(Owner::Opponent),
+            (false, false) => IslandStatus::Unclaimed,
+        }
+    }
+
+    /// Whether the island ends up the same without sending any more robots there
+    ///
+    /// A decided island is settled when its owner has no neutral fields left to take,
+    /// or already has robots there to take them.
+    pub fn is_settled(&self) -> bool {
+        match self.status() {
+            IslandStatus::Contested => false,
+            IslandStatus::Decided(owner) => self.neutral_fields == 0 || self.units(owner) > 0,
+            IslandStatus::Unclaimed => true,
+        }
+    }
+
+    pub fn fields(&self, owner: Owner) -> u32 {
+        match owner {
+            Owner::Me => self.my_fields,
+            Owner::Opponent => self.opponent_fields,
+            Owner::Neutral => self.neutral_fields,
+        }
+    }
+
+    pub fn units(&self, owner: Owner) -> u32 {
+        match owner {
+            Owner::Me => self.my_units,
+            Owner::Opponent => self.opponent_units,
+            Owner::Neutral => 0,
+        }
+    }
+}
+
+impl RegionBoard {
+
+    pub fn new(board: &Board) -> Self {
+        let mut regions = Grid::filled(board.width, board.height, None);
+        let mut islands = Vec::new();
+
+        for field in board.fields.iter() {
+            if !field.is_traversible() || regions[field.coord()].is_some() {
+                continue;
+            }
+
+            // flood fill the island this field is on
+            let id = islands.len();
+            let mut island = Island::default();
+            let mut stack = vec![field.coord()];
+            regions[field.coord()] = Some(id);
+
+            while let Some(coord) = stack.pop() {
+                let current = &board.fields[coord.index(board.width)];
+                island.size += 1;
+                island.scrap += current.scrap_amount;
+                match current.owner {
+                    Owner::Me => {
+                        island.my_fields += 1;
+                        island.my_units += current.num_units;
+                    },
+                    Owner::Opponent => {
+                        island.opponent_fields += 1;
+                        island.opponent_units += current.num_units;
+                    },
+                    Owner::Neutral => island.neutral_fields += 1,
+                }
+
+                for neighbour in coord.neighbours(board.width, board.height) {
+                    if board.fields[neighbour.index(board.width)].is_traversible() && regions[neighbour].is_none() {
+                        regions[neighbour] = Some(id);
+                        stack.push(neighbour);
+                    }
+                }
+            }
+
+            islands.push(island);
+        }
+
+        Self {
+            regions,
+            islands,
+        }
+    }
+
+    /// The island a field is on, `None` for grass and recyclers
+    pub fn island_at(&self, x: u32, y: u32) -> Option<&Island> {
+        self.regions
+            .get(Coord::new(x, y))
+            .copied()
+            .flatten()
+            .map(|x| &self.islands[x])
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.islands.len() <= 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grass_and_recyclers_split_islands() {
+        let board = Board::from_ascii("
+            3m1 . 4  4o
+            2   . 4oR 5o2
+            1m  . 1  .
+        ").unwrap();
+        let regions = RegionBoard::new(&board);
+
+        assert_eq!(regions.islands.len(), 3);
+        assert_eq!(regions.island_at(1, 0), None);
+        assert_eq!(regions.island_at(2, 1), None);
+        assert_eq!(regions.island_at(0, 0), Some(&Island {
+            size: 3,
+            my_fields: 2,
+            opponent_fields: 0,
+            neutral_fields: 1,
+            my_units: 1,
+            opponent_units: 0,
+            scrap: 6,
+        }));
+        assert_eq!(regions.island_at(3, 1).unwrap().size, 3);
+        assert_eq!(regions.island_at(2, 2).unwrap().size, 1);
+    }
+
+    #[test]
+    fn islands_are_settled_when_nobody_else_can_get_in() {
+        let board = Board::from_ascii("
+            3m1 3  . 3m 3 . 3o 3m . 3
+        ").unwrap();
+        let regions = RegionBoard::new(&board);
+        let status = |x| {
+            let island = regions.island_at(x, 0).unwrap();
+            (island.status(), island.is_settled())
+        };
+
+        assert_eq!(status(0), (IslandStatus::Decided(Owner::Me), true));
+        assert_eq!(status(3), (IslandStatus::Decided(Owner::Me), false));
+        assert_eq!(status(6), (IslandStatus::Contested, false));
+        assert_eq!(status(9), (IslandStatus::Unclaimed, true));
+    }
+}
@@ -0,0 +1,297 @@
+//! Draws boards and per-field overlays as text, for debugging agents
+//!
+//! `RenderMode::Ansi` colours the fields with 24-bit ANSI escapes, for terminals.
+//! `RenderMode::Plain` only uses text, for CodinGame's stderr panel and log files.
+//!
+//! Boards are drawn with the tokens of the ASCII notation. An overlay draws one value per field,
+//! followed by the owner letter of the field; in ANSI mode the background shows the value
+//! as a heat map, blue for the lowest value and red for the highest.
+
+use super::ascii::field_token;
+use super::distance_board::ManhattanDistance;
+use super::mine_duration_board::MineDuration;
+use super::territory_board::Control;
+use super::{Board, Field, Grid, Owner};
+
+const RESET: &str = "\x1b[0m";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RenderMode {
+    Ansi,
+    Plain,
+}
+
+/// A value that can be drawn on an overlay
+pub trait HeatValue {
+    /// The number the colour is based on, `None` for fields without a value
+    fn heat(&self) -> Option<f32>;
+
+    fn label(&self) -> String;
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Renderer {
+    pub mode: RenderMode,
+}
+
+impl Renderer {
+
+    pub fn new(mode: RenderMode) -> Self {
+        Self { mode }
+    }
+
+    pub fn ansi() -> Self {
+        Self::new(RenderMode::Ansi)
+    }
+
+    pub fn plain() -> Self {
+        Self::new(RenderMode::Plain)
+    }
+
+    pub fn render_board(&self, board: &Board) -> String {
+        let labels = board.fields
+            .iter()
+            .map(field_token)
+            .collect::<Vec<_>>();
+        let colours = board.fields
+            .iter()
+            .map(|x| Some(owner_colour(x)))
+            .collect::<Vec<_>>();
+
+        let mut result = format!("my_matter {} opponent_matter {}\n", board.my_matter, board.opponent_matter);
+        result.push_str(&self.render_cells(board.width, &labels, &colours));
+        result
+    }
+
+    /// Draws `overlay` on top of `board`; they must be the same size
+    pub fn render_overlay<T: HeatValue>(&self, board: &Board, overlay: &Grid<T>) -> String {
+        assert!(overlay.width() == board.width && overlay.height() == board.height, "overlay differs in size from the board");
+
+        let heats = overlay.iter().map(HeatValue::heat).collect::<Vec<_>>();
+        let (min, max) = heats
+            .iter()
+            .flatten()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), x| (min.min(*x), max.max(*x)));
+
+        let labels = overlay
+            .iter()
+            .zip(board.fields.iter())
+            .map(|(value, field)| format!("{}{}", value.label(), owner_letter(field.owner)))
+            .collect::<Vec<_>>();
+        let colours = heats
+            .iter()
+            .zip(board.fields.iter())
+            .map(|(heat, field)| match heat {
+                Some(heat) => Some(heat_colour(*heat, min, max)),
+                None if field.is_grass() => Some(owner_colour(field)),
+                None => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut result = if min <= max {
+            format!("min {} max {}\n", min, max)
+        } else {
+            "no values\n".to_string()
+        };
+        result.push_str(&self.render_cells(board.width, &labels, &colours));
+        result
+    }
+
+    fn render_cells(&self, width: u32, labels: &[String], colours: &[Option<(u8, u8, u8)>]) -> String {
+        let cell_width = labels.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+        let mut result = String::new();
+
+        for (labels, colours) in labels.chunks(width.max(1) as usize).zip(colours.chunks(width.max(1) as usize)) {
+            let row = labels
+                .iter()
+                .zip(colours)
+                .map(|(label, colour)| {
+                    let cell = format!("{:>width$}", label, width = cell_width);
+                    match (self.mode, colour) {
+                        (RenderMode::Ansi, Some(colour)) => paint(&cell, *colour),
+                        _ => cell,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            match self.mode {
+                RenderMode::Ansi => result.push_str(&row.concat()),
+                RenderMode::Plain => result.push_str(row.join(" ").trim_end()),
+            }
+            result.push('\n');
+        }
+
+        result
+    }
+}
+
+/// Pads the cell on both sides so the colours of neighbouring fields don't touch the text
+fn paint(cell: &str, (r, g, b): (u8, u8, u8)) -> String {
+    // dark text on light backgrounds and vice versa
+    let brightness = 299 * r as u32 + 587 * g as u32 + 114 * b as u32;
+    let foreground = if brightness > 128_000 { "30" } else { "97" };
+    format!("\x1b[{};48;2;{};{};{}m {} {}", foreground, r, g, b, cell, RESET)
+}
+
+fn owner_colour(field: &Field) -> (u8, u8, u8) {
+    if field.is_grass() {
+        return (20, 60, 20);
+    }
+
+    match field.owner {
+        Owner::Me => (40, 90, 200),
+        Owner::Opponent => (190, 50, 50),
+        Owner::Neutral => (90, 90, 90),
+    }
+}
+
+fn owner_letter(owner: Owner) -> char {
+    match owner {
+        Owner::Me => 'm',
+        Owner::Opponent => 'o',
+        Owner::Neutral => ' ',
+    }
+}
+
+/// Blue at `min`, through green and yellow, to red at `max`
+fn heat_colour(heat: f32, min: f32, max: f32) -> (u8, u8, u8) {
+    let t = if max > min { (heat - min) / (max - min) } else { 0.5 };
+    let stops = [(40.0, 80.0, 220.0), (40.0, 180.0, 90.0), (230.0, 210.0, 50.0), (220.0, 50.0, 40.0)];
+
+    let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let index = (scaled as usize).min(stops.len() - 2);
+    let fraction = scaled - index as f32;
+    let (from, to) = (stops[index], stops[index + 1]);
+    let mix = |a: f32, b: f32| (a + (b - a) * fraction).round() as u8;
+
+    (mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+impl HeatValue for u32 {
+    fn heat(&self) -> Option<f32> {
+        Some(*self as f32)
+    }
+
+    fn label(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl HeatValue for i32 {
+    fn heat(&self) -> Option<f32> {
+        Some(*self as f32)
+    }
+
+    fn label(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl HeatValue for f32 {
+    fn heat(&self) -> Option<f32> {
+        Some(*self)
+    }
+
+    fn label(&self) -> String {
+        format!("{:.1}", self)
+    }
+}
+
+impl HeatValue for bool {
+    fn heat(&self) -> Option<f32> {
+        Some(if *self { 1.0 } else { 0.0 })
+    }
+
+    fn label(&self) -> String {
+        (if *self { "x" } else { "." }).to_string()
+    }
+}
+
+impl HeatValue for ManhattanDistance {
+    fn heat(&self) -> Option<f32> {
+        match self {
+            ManhattanDistance::Dist(x) => Some(*x as f32),
+            ManhattanDistance::Unreachable => None,
+        }
+    }
+
+    fn label(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl HeatValue for MineDuration {
+    fn heat(&self) -> Option<f32> {
+        match self {
+            MineDuration::Duration(x) => Some(*x as f32),
+            MineDuration::Unending => None,
+        }
+    }
+
+    fn label(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Our lead in turns, positive where we are first
+impl HeatValue for Control {
+    fn heat(&self) -> Option<f32> {
+        // a field one side can't reach at all shouldn't wash out the colours of the front
+        self.margin().map(|x| x.clamp(-10, 10) as f32)
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Control::Mine(u32::MAX) => "M".to_string(),
+            Control::Theirs(u32::MAX) => "T".to_string(),
+            Control::Mine(x) => format!("+{}", x),
+            Control::Theirs(x) => format!("-{}", x),
+            Control::Contested => "0".to_string(),
+            Control::Unreachable => "-".to_string(),
+        }
+    }
+}
+
+impl<T: HeatValue> HeatValue for Option<T> {
+    fn heat(&self) -> Option<f32> {
+        self.as_ref().and_then(HeatValue::heat)
+    }
+
+    fn label(&self) -> String {
+        self.as_ref().map_or_else(|| "-".to_string(), HeatValue::label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::distance_board::DistanceBoard;
+
+    #[test]
+    fn plain_overlay_shows_values_and_owners() {
+        let board = Board::from_ascii("
+            1m1 1 .
+            1   1 1o1
+        ").unwrap();
+        let distances = DistanceBoard::from_owner(&board, Owner::Me);
+
+        let rendered = Renderer::plain().render_overlay(&board, &distances.distances);
+        assert_eq!(rendered, "min 0 max 3\n0m 1  -\n1  2  3o\n");
+    }
+
+    #[test]
+    fn ansi_output_is_coloured_and_reset() {
+        let board = Board::from_ascii("1m1 .").unwrap();
+
+        let rendered = Renderer::ansi().render_board(&board);
+        assert!(rendered.contains("\x1b[97;48;2;40;90;200m"));
+        assert_eq!(rendered.matches(RESET).count(), 2);
+        assert!(!Renderer::plain().render_board(&board).contains('\x1b'));
+    }
+
+    #[test]
+    fn heat_runs_from_blue_to_red() {
+        assert_eq!(heat_colour(0.0, 0.0, 10.0), (40, 80, 220));
+        assert_eq!(heat_colour(10.0, 0.0, 10.0), (220, 50, 40));
+    }
+}
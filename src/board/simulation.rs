@@ -0,0 +1,303 @@
+use std::cmp::min;
+use std::collections::VecDeque;
+use super::super::action::Action;
+use super::{Board, Coord, Owner};
+
+pub const ROBOT_COST: u32 = 10;
+pub const RECYCLER_COST: u32 = 10;
+pub const BASE_INCOME: u32 = 10;
+
+impl Board {
+
+    /// Simulates one full game turn and returns the board at the start of the next turn.
+    ///
+    /// Follows the order of the referee:
+    /// builds, then spawns and moves (simultaneously), fights, marking tiles,
+    /// harvesting, tiles turning to grass and finally the base income.
+    /// Invalid actions are skipped, like the server does.
+    pub fn apply_turn(&self, my_actions: &[Action], opponent_actions: &[Action]) -> Self {
+        let mut board = self.clone();
+
+        board.apply_builds(my_actions, Owner::Me);
+        board.apply_builds(opponent_actions, Owner::Opponent);
+
+        // Spawns and moves of both players don't interact, so they can be done one player at a time
+        let mut my_units = board.spawn_and_move(my_actions, Owner::Me);
+        let mut opponent_units = board.spawn_and_move(opponent_actions, Owner::Opponent);
+
+        // Fights: robots of opposing owners remove each other one for one
+        // Remaining robots mark the tile they're on
+        for (i, field) in board.fields.iter_mut().enumerate() {
+            let casualties = min(my_units[i], opponent_units[i]);
+            my_units[i] -= casualties;
+            opponent_units[i] -= casualties;
+
+            field.num_units = my_units[i] + opponent_units[i];
+            if my_units[i] > 0 {
+                field.owner = Owner::Me;
+            } else if opponent_units[i] > 0 {
+                field.owner = Owner::Opponent;
+            }
+        }
+
+        let mut board = board.process_harvest_cycle();
+        board.clear_grass();
+
+        board.my_matter += BASE_INCOME;
+        board.opponent_matter += BASE_INCOME;
+
+        board.update_recycler_range();
+        board.update_build_spawn_flags();
+        board
+    }
+
+    /// Recomputes `can_build` and `can_spawn` from our own perspective
+    pub fn update_build_spawn_flags(&mut self) {
+        for field in self.fields.iter_mut() {
+            let mine = field.owner == Owner::Me && !field.is_grass() && !field.has_recycler;
+            field.can_spawn = mine;
+            field.can_build = mine && field.num_units == 0;
+        }
+    }
+
+    /// Applies the spawns and moves of `owner`, paying for the spawns.
+    ///
+    /// Returns the number of robots of `owner` on every field before fights, in field order.
+    /// The board should already have this turn's recyclers, as they block paths.
+    pub fn spawn_and_move(&mut self, actions: &[Action], owner: Owner) -> Vec<u32> {
+        // Units that were on the board at the start of the turn; spawned units can't move
+        let mut movable = self.fields
+            .iter()
+            .map(|f| if f.owner == owner { f.num_units } else { 0 })
+            .collect::<Vec<_>>();
+        let mut units = movable.clone();
+
+        self.apply_spawns(actions, owner, &mut units);
+        self.apply_moves(actions, &mut movable, &mut units);
+        units
+    }
+
+    /// Places the recyclers `owner` builds this turn, paying for them
+    pub fn apply_builds(&mut self, actions: &[Action], owner: Owner) {
+        for action in actions {
+            if let Action::Build(x, y) = *action {
+                if self.matter(owner) < RECYCLER_COST {
+                    continue;
+                }
+
+                let field = match self.get_field_mut(x, y) {
+                    Some(field) => field,
+                    None => continue,
+                };
+                if field.owner != owner || field.is_grass() || field.has_recycler || field.num_units > 0 {
+                    continue;
+                }
+
+                field.has_recycler = true;
+                *self.matter_mut(owner) -= RECYCLER_COST;
+            }
+        }
+    }
+
+    fn apply_spawns(&mut self, actions: &[Action], owner: Owner, units: &mut [u32]) {
+        for action in actions {
+            if let Action::Spawn(amount, x, y) = *action {
+                if amount == 0 || self.matter(owner) < amount * ROBOT_COST {
+                    continue;
+                }
+
+                let field = match self.get_field(x, y) {
+                    Some(field) => field,
+                    None => continue,
+                };
+                if field.owner != owner || !field.is_traversible() {
+                    continue;
+                }
+
+                units[(x + y * self.width) as usize] += amount;
+                *self.matter_mut(owner) -= amount * ROBOT_COST;
+            }
+        }
+    }
+
+    fn apply_moves(&self, actions: &[Action], movable: &mut [u32], units: &mut [u32]) {
+        for action in actions {
+            if let Action::Move { amount, from, to } = *action {
+                if self.get_field(from.0, from.1).is_none() || self.get_field(to.0, to.1).is_none() {
+                    continue;
+                }
+
+                let from_index = (from.0 + from.1 * self.width) as usize;
+                let amount = min(amount, movable[from_index]);
+                if amount == 0 {
+                    continue;
+                }
+
+                if let Some((x, y)) = self.next_step(from, to) {
+                    movable[from_index] -= amount;
+                    units[from_index] -= amount;
+                    units[(x + y * self.width) as usize] += amount;
+                }
+            }
+        }
+    }
+
+    /// The tile a robot at `from` steps onto when it is ordered to move to `to`.
+    ///
+    /// Robots follow a shortest path over traversible tiles.
+    /// Returns `None` when the robot stays where it is.
+    pub fn next_step(&self, from: (u32, u32), to: (u32, u32)) -> Option<(u32, u32)> {
+        if from == to {
+            return None;
+        }
+
+        let (from, to) = (Coord::from(from), Coord::from(to));
+        if from.direction_to(to).is_some() {
+            return if self.get_field(to.x, to.y)?.is_traversible() { Some(to.into()) } else { None };
+        }
+
+        // Breadth first search from the target, so every tile knows its distance to it
+        let mut distances = vec![u32::MAX; self.fields.len()];
+        let mut frontier = VecDeque::new();
+        distances[to.index(self.width)] = 0;
+        frontier.push_back(to);
+
+        while let Some(current) = frontier.pop_front() {
+            let distance = distances[current.index(self.width)];
+            for neighbour in current.neighbours(self.width, self.height) {
+                let index = neighbour.index(self.width);
+                if distances[index] == u32::MAX && self.fields[index].is_traversible() {
+                    distances[index] = distance + 1;
+                    frontier.push_back(neighbour);
+                }
+            }
+        }
+
+        from
+            .neighbours(self.width, self.height)
+            .filter(|x| distances[x.index(self.width)] != u32::MAX)
+            .min_by_key(|x| distances[x.index(self.width)])
+            .map(|x| x.into())
+    }
+
+    pub fn matter(&self, owner: Owner) -> u32 {
+        match owner {
+            Owner::Me => self.my_matter,
+            Owner::Opponent => self.opponent_matter,
+            Owner::Neutral => 0,
+        }
+    }
+
+    fn matter_mut(&mut self, owner: Owner) -> &mut u32 {
+        match owner {
+            Owner::Me => &mut self.my_matter,
+            Owner::Opponent => &mut self.opponent_matter,
+            Owner::Neutral => panic!("neutral has no matter"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_happen_before_moves() {
+        let board = Board::from_ascii("
+            my_matter 10
+            5m1 5m 5
+        ").unwrap();
+
+        // the new recycler is in the way, so the robot stays where it is
+        let next = board.apply_turn(&[Action::Build(1, 0), Action::Move { amount: 1, from: (0, 0), to: (2, 0) }], &[]);
+        assert!(next.fields[1].has_recycler);
+        assert_eq!(next.fields[0].num_units, 1);
+        assert_eq!(next.fields[2].owner, Owner::Neutral);
+    }
+
+    #[test]
+    fn robots_fight_one_for_one_and_mark_their_fields() {
+        let board = Board::from_ascii("
+            5m3 5 5o1
+            5m1 5 5o1
+        ").unwrap();
+        let my_actions = [Action::Move { amount: 3, from: (0, 0), to: (1, 0) }, Action::Move { amount: 1, from: (0, 1), to: (1, 1) }];
+        let opponent_actions = [Action::Move { amount: 1, from: (2, 0), to: (1, 0) }, Action::Move { amount: 1, from: (2, 1), to: (1, 1) }];
+
+        let next = board.apply_turn(&my_actions, &opponent_actions);
+        assert_eq!((next.fields[1].owner, next.fields[1].num_units), (Owner::Me, 2));
+        // a draw leaves nobody to mark the field
+        assert_eq!((next.fields[4].owner, next.fields[4].num_units), (Owner::Neutral, 0));
+        // fields that are left keep their owner
+        assert_eq!((next.fields[0].owner, next.fields[0].num_units), (Owner::Me, 0));
+    }
+
+    #[test]
+    fn recyclers_harvest_one_scrap_per_field_for_every_player_in_range() {
+        let board = Board::from_ascii("
+            5mR 3 5oR
+        ").unwrap();
+
+        let next = board.apply_turn(&[], &[]);
+        assert_eq!(next.fields.iter().map(|f| f.scrap_amount).collect::<Vec<_>>(), vec![4, 2, 4]);
+        // the shared field pays both players
+        assert_eq!((next.my_matter, next.opponent_matter), (2 + BASE_INCOME, 2 + BASE_INCOME));
+    }
+
+    #[test]
+    fn recyclers_at_the_edges_do_not_harvest_the_next_row() {
+        let scraps = |board: Board| board.process_harvest_cycle().fields.iter().map(|f| f.scrap_amount).collect::<Vec<_>>();
+
+        // (0, 1) comes right after (1, 0) in field order, but isn't next to it
+        assert_eq!(scraps(Board::from_ascii("
+            5 5mR
+            5 5
+        ").unwrap()), vec![4, 4, 5, 4]);
+        assert_eq!(scraps(Board::from_ascii("
+            5   5
+            5mR 5
+        ").unwrap()), vec![4, 5, 4, 4]);
+    }
+
+    #[test]
+    fn fields_that_turn_to_grass_lose_their_robots_and_recyclers() {
+        let board = Board::from_ascii("
+            1mR 1m2 5o
+        ").unwrap();
+
+        let next = board.apply_turn(&[], &[]);
+        for field in next.fields[..2].iter() {
+            assert!(field.is_grass());
+            assert!(!field.has_recycler);
+            assert_eq!((field.owner, field.num_units), (Owner::Neutral, 0));
+        }
+        assert_eq!(next.fields[2].scrap_amount, 5);
+    }
+
+    #[test]
+    fn both_players_get_the_base_income() {
+        let board = Board::from_ascii("
+            my_matter 25
+            opponent_matter 3
+            5m1 5 5o1
+        ").unwrap();
+
+        let next = board.apply_turn(&[], &[]);
+        assert_eq!((next.my_matter, next.opponent_matter), (25 + BASE_INCOME, 3 + BASE_INCOME));
+    }
+
+    #[test]
+    fn next_step_follows_a_shortest_path_or_stays() {
+        let board = Board::from_ascii("
+            5m1 . 5 . 5
+            5   5 5 . 5
+        ").unwrap();
+
+        assert_eq!(board.next_step((0, 0), (2, 0)), Some((0, 1)));
+        // walled off by grass
+        assert_eq!(board.next_step((0, 0), (4, 0)), None);
+        // an adjacent target that is grass
+        assert_eq!(board.next_step((0, 0), (1, 0)), None);
+        assert_eq!(board.next_step((0, 0), (0, 0)), None);
+    }
+}
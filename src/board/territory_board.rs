@@ -0,0 +1,159 @@
+use super::distance_board::{DistanceBoard, ManhattanDistance};
+use super::{Board, Coord, Grid, Owner};
+
+/// Which player gets to each field first, like a Voronoi diagram of the robots
+///
+/// Distances are counted the same way as in `DistanceBoard`:
+/// 0 on fields with robots, 1 on other owned fields, where a robot can be spawned.
+#[derive(Clone, Debug)]
+pub struct TerritoryBoard {
+    pub mine: DistanceBoard,
+    pub theirs: DistanceBoard,
+    pub control: Grid<Control>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Control {
+    /// We get there first, by this many turns; `u32::MAX` when the opponent can't get there at all
+    Mine(u32),
+    /// The opponent gets there first, by this many turns; `u32::MAX` when we can't get there at all
+    Theirs(u32),
+    /// Both players can get there on the same turn
+    Contested,
+    /// Nobody can get there
+    Unreachable,
+}
+
+impl Control {
+
+    /// How many turns earlier we get there than the opponent, negative when they are first
+    pub fn margin(self) -> Option<i32> {
+        match self {
+            Control::Mine(x) => Some(x.min(i32::MAX as u32) as i32),
+            Control::Theirs(x) => Some(-(x.min(i32::MAX as u32) as i32)),
+            Control::Contested => Some(0),
+            Control::Unreachable => None,
+        }
+    }
+
+    /// The player that gets there first
+    pub fn owner(self) -> Option<Owner> {
+        match self {
+            Control::Mine(_) => Some(Owner::Me),
+            Control::Theirs(_) => Some(Owner::Opponent),
+            Control::Contested | Control::Unreachable => None,
+        }
+    }
+}
+
+impl TerritoryBoard {
+
+    pub fn new(board: &Board) -> Self {
+        let mut distances = [
+            vec![ManhattanDistance::Unreachable; board.fields.len()],
+            vec![ManhattanDistance::Unreachable; board.fields.len()],
+        ];
+        let owners = [Owner::Me, Owner::Opponent];
+
+        // Both searches advance one turn at a time, (side, field index) per layer
+        let mut layer = Vec::new();
+        let mut next_layer = Vec::new();
+        for (side, owner) in owners.iter().enumerate() {
+            for (i, field) in board.fields.iter().enumerate() {
+                if field.owner == *owner && field.num_units > 0 {
+                    distances[side][i] = ManhattanDistance::Dist(0);
+                    layer.push((side, i));
+                } else if field.owner == *owner && field.is_traversible() {
+                    distances[side][i] = ManhattanDistance::Dist(1);
+                    next_layer.push((side, i));
+                }
+            }
+        }
+
+        let mut distance = 0;
+        while !layer.is_empty() || !next_layer.is_empty() {
+            for (side, i) in layer.drain(..) {
+                let coord = Coord::from_index(i, board.width);
+                for neighbour in coord.neighbours(board.width, board.height) {
+                    let j = neighbour.index(board.width);
+                    if board.fields[j].is_traversible() && distances[side][j].is_unreachable() {
+                        distances[side][j] = ManhattanDistance::Dist(distance + 1);
+                        next_layer.push((side, j));
+                    }
+                }
+            }
+
+            std::mem::swap(&mut layer, &mut next_layer);
+            distance += 1;
+        }
+
+        let [mine, theirs] = distances.map(|x| DistanceBoard { distances: Grid::new(board.width, board.height, x) });
+        let control = mine.distances.zip_with(&theirs.distances, |a, b| match (a, b) {
+            (ManhattanDistance::Unreachable, ManhattanDistance::Unreachable) => Control::Unreachable,
+            (ManhattanDistance::Dist(_), ManhattanDistance::Unreachable) => Control::Mine(u32::MAX),
+            (ManhattanDistance::Unreachable, ManhattanDistance::Dist(_)) => Control::Theirs(u32::MAX),
+            (ManhattanDistance::Dist(a), ManhattanDistance::Dist(b)) if a < b => Control::Mine(b - a),
+            (ManhattanDistance::Dist(a), ManhattanDistance::Dist(b)) if a > b => Control::Theirs(a - b),
+            _ => Control::Contested,
+        });
+
+        Self {
+            mine,
+            theirs,
+            control,
+        }
+    }
+
+    pub fn get_field(&self, x: u32, y: u32) -> Option<&Control> {
+        self.control.get(Coord::new(x, y))
+    }
+
+    /// The number of fields `owner` would end up with if both players took what they reach first
+    ///
+    /// Fields nobody can reach, like recyclers, keep their current owner; contested fields count for nobody.
+    pub fn projected_territory(&self, board: &Board, owner: Owner) -> u32 {
+        board.fields
+            .iter()
+            .zip(self.control.iter())
+            .filter(|(f, _)| !f.is_grass())
+            .filter(|(f, control)| match control {
+                Control::Unreachable => f.owner == owner,
+                control => control.owner() == Some(owner),
+            })
+            .count() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_go_to_whoever_is_closest() {
+        let board = Board::from_ascii("
+            5m1 5 5 5 5 5o1
+            5m  . 5 5 . 5oR
+        ").unwrap();
+        let territory = TerritoryBoard::new(&board);
+
+        assert_eq!(territory.get_field(1, 0), Some(&Control::Mine(3)));
+        assert_eq!(territory.get_field(2, 1), Some(&Control::Mine(1)));
+        assert_eq!(territory.get_field(3, 1), Some(&Control::Theirs(1)));
+        assert_eq!(territory.get_field(1, 1), Some(&Control::Unreachable));
+        assert_eq!(territory.projected_territory(&board, Owner::Me), 5);
+        assert_eq!(territory.projected_territory(&board, Owner::Opponent), 5);
+    }
+
+    #[test]
+    fn distances_match_the_distance_board() {
+        let board = Board::from_ascii("
+            5m2 5m  5 . 5
+            5   5   5 5 5o
+            5   .   5 5o3 5
+        ").unwrap();
+        let territory = TerritoryBoard::new(&board);
+
+        assert_eq!(territory.mine.distances, DistanceBoard::from_owner(&board, Owner::Me).distances);
+        assert_eq!(territory.theirs.distances, DistanceBoard::from_owner(&board, Owner::Opponent).distances);
+    }
+}
@@ -0,0 +1,157 @@
+//! Scores boards for the search agents, and anything else that needs to compare positions
+//!
+//! Every evaluator looks at the board from the side of one owner, higher is better,
+//! and is zero-sum: swapping the owner gives the negated score.
+
+use super::board::territory_board::TerritoryBoard;
+use super::board::yield_board::YieldBoard;
+use super::board::{Board, Owner};
+
+pub trait Evaluator {
+    /// How good `board` is for `owner`
+    fn evaluate(&self, board: &Board, owner: Owner) -> f32;
+}
+
+/// Any function with the right signature is an evaluator, for one-off features and tuning
+impl<F: Fn(&Board, Owner) -> f32> Evaluator for F {
+    fn evaluate(&self, board: &Board, owner: Owner) -> f32 {
+        self(board, owner)
+    }
+}
+
+/// The difference in fields owned right now, which decides the game at the end
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TerritoryDifference;
+
+impl Evaluator for TerritoryDifference {
+    fn evaluate(&self, board: &Board, owner: Owner) -> f32 {
+        board.territory_count(owner) as f32 - board.territory_count(owner.swapped()) as f32
+    }
+}
+
+/// The difference in robots
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RobotDifference;
+
+impl Evaluator for RobotDifference {
+    fn evaluate(&self, board: &Board, owner: Owner) -> f32 {
+        board.robot_count(owner) as f32 - board.robot_count(owner.swapped()) as f32
+    }
+}
+
+/// The difference in `matter + 10 * robots + discounted yield of the recyclers`
+///
+/// This is what `SimpleEconomyAgent` compares to decide whether it needs more recyclers.
+#[derive(Copy, Clone, Debug)]
+pub struct MatterRobotScore {
+    /// How much of what the recyclers would mine on their own is expected to come in
+    pub expected_mining_discount: f32,
+}
+
+impl Default for MatterRobotScore {
+    fn default() -> Self {
+        Self {
+            expected_mining_discount: 0.8,
+        }
+    }
+}
+
+impl MatterRobotScore {
+
+    /// The score of `owner` alone
+    pub fn score(&self, board: &Board, yield_board: &YieldBoard, owner: Owner) -> u32 {
+        let recycler_yield = board.fields
+            .iter()
+            .zip(yield_board.prospective_scrap.iter())
+            .filter(|(f, _)| f.has_recycler && f.owner == owner)
+            .map(|(_, y)| *y)
+            .sum::<u32>();
+
+        board.matter(owner) + 10 * board.robot_count(owner) + (recycler_yield as f32 * self.expected_mining_discount) as u32
+    }
+}
+
+impl Evaluator for MatterRobotScore {
+    fn evaluate(&self, board: &Board, owner: Owner) -> f32 {
+        // yields ignoring all recyclers, as if every recycler mined its fields on its own
+        let yield_board = YieldBoard::without_recycling(board);
+        self.score(board, &yield_board, owner) as f32 - self.score(board, &yield_board, owner.swapped()) as f32
+    }
+}
+
+/// The difference in fields each player would end up with by taking what they reach first
+#[derive(Copy, Clone, Debug, Default)]
+pub struct VoronoiTerritory;
+
+impl Evaluator for VoronoiTerritory {
+    fn evaluate(&self, board: &Board, owner: Owner) -> f32 {
+        let territory_board = TerritoryBoard::new(board);
+        territory_board.projected_territory(board, owner) as f32
+            - territory_board.projected_territory(board, owner.swapped()) as f32
+    }
+}
+
+/// A weighted sum of other evaluators
+#[derive(Default)]
+pub struct WeightedBlend {
+    pub terms: Vec<(f32, Box<dyn Evaluator>)>,
+}
+
+impl WeightedBlend {
+
+    pub fn with(mut self, weight: f32, evaluator: impl Evaluator + 'static) -> Self {
+        self.terms.push((weight, Box::new(evaluator)));
+        self
+    }
+}
+
+impl Evaluator for WeightedBlend {
+    fn evaluate(&self, board: &Board, owner: Owner) -> f32 {
+        self.terms
+            .iter()
+            .map(|(weight, evaluator)| weight * evaluator.evaluate(board, owner))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluators_are_zero_sum() {
+        let board = Board::from_ascii("
+            5m1 5m 5 . 5o2
+            5m  5  5 5 5oR
+        ").unwrap();
+        let board = Board { my_matter: 12, opponent_matter: 30, ..board };
+        let evaluators: Vec<Box<dyn Evaluator>> = vec![
+            Box::new(TerritoryDifference),
+            Box::new(RobotDifference),
+            Box::new(MatterRobotScore::default()),
+            Box::new(VoronoiTerritory),
+        ];
+
+        for evaluator in evaluators.iter() {
+            assert_eq!(evaluator.evaluate(&board, Owner::Me), -evaluator.evaluate(&board, Owner::Opponent));
+        }
+        assert_eq!(TerritoryDifference.evaluate(&board, Owner::Me), 1.0);
+        assert_eq!(RobotDifference.evaluate(&board, Owner::Me), -1.0);
+        // 12 + 10 against 30 + 20 + 0.8 * (5 + 5 + 5)
+        assert_eq!(MatterRobotScore::default().evaluate(&board, Owner::Me), -40.0);
+        // grass and its own recycler wall the opponent's robots in, so the rest of the map is ours
+        assert_eq!(VoronoiTerritory.evaluate(&board, Owner::Me), 7.0 - 2.0);
+    }
+
+    #[test]
+    fn blends_add_up_their_terms() {
+        let board = Board::from_ascii("
+            5m1 5m 5 5o1
+        ").unwrap();
+        let blend = WeightedBlend::default()
+            .with(2.0, TerritoryDifference)
+            .with(0.5, |board: &Board, _| board.fields.len() as f32);
+
+        assert_eq!(blend.evaluate(&board, Owner::Me), 2.0 * 1.0 + 0.5 * 4.0);
+    }
+}
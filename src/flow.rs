@@ -0,0 +1,131 @@
+//! The residual graph shared by the flow problems of `inference` and `assignment`
+//!
+//! Every edge is stored next to its reverse edge, so `edge ^ 1` is the reverse of `edge`.
+//! Finding the augmenting paths is up to the caller: a breadth first search for maximum flow,
+//! Bellman-Ford for minimum cost flow.
+
+pub const INFINITE: i64 = i64::MAX / 4;
+
+pub struct FlowNetwork {
+    edges: Vec<Edge>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+struct Edge {
+    to: usize,
+    capacity: i64,
+    cost: i64,
+    flow: i64,
+}
+
+impl FlowNetwork {
+
+    pub fn new(nodes: usize) -> Self {
+        Self {
+            edges: Vec::new(),
+            adjacency: vec![Vec::new(); nodes],
+        }
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, capacity: i64) -> usize {
+        self.add_edge_with_cost(from, to, capacity, 0)
+    }
+
+    pub fn add_edge_with_cost(&mut self, from: usize, to: usize, capacity: i64, cost: i64) -> usize {
+        let index = self.edges.len();
+        self.edges.push(Edge { to, capacity, cost, flow: 0 });
+        self.edges.push(Edge { to: from, capacity: 0, cost: -cost, flow: 0 });
+        self.adjacency[from].push(index);
+        self.adjacency[to].push(index + 1);
+        index
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// The edges leaving `node`, including the reverse edges of the ones entering it
+    pub fn edges_from(&self, node: usize) -> &[usize] {
+        &self.adjacency[node]
+    }
+
+    pub fn from(&self, edge: usize) -> usize {
+        self.edges[edge ^ 1].to
+    }
+
+    pub fn to(&self, edge: usize) -> usize {
+        self.edges[edge].to
+    }
+
+    pub fn cost(&self, edge: usize) -> i64 {
+        self.edges[edge].cost
+    }
+
+    pub fn residual(&self, edge: usize) -> i64 {
+        self.edges[edge].capacity - self.edges[edge].flow
+    }
+
+    pub fn flow(&self, edge: usize) -> i64 {
+        self.edges[edge].flow
+    }
+
+    pub fn outflow(&self, node: usize) -> i64 {
+        self.adjacency[node].iter().map(|e| self.flow(*e).max(0)).sum()
+    }
+
+    /// Whether any edge was added towards `node`
+    pub fn has_incoming(&self, node: usize) -> bool {
+        self.adjacency[node].iter().any(|e| self.edges[*e ^ 1].capacity > 0)
+    }
+
+    pub fn push(&mut self, edge: usize, amount: i64) {
+        self.edges[edge].flow += amount;
+        self.edges[edge ^ 1].flow -= amount;
+    }
+
+    /// Pushes as much flow as fits along every path `find_path` finds, until it finds none
+    ///
+    /// `find_path` gives the edge every node was reached by, `usize::MAX` for the ones it didn't reach,
+    /// or `None` to stop early.
+    pub fn augment(&mut self, source: usize, sink: usize, mut find_path: impl FnMut(&Self) -> Option<Vec<usize>>) {
+        while let Some(parent_edge) = find_path(self) {
+            if parent_edge[sink] == usize::MAX {
+                return;
+            }
+
+            let mut path = Vec::new();
+            let mut node = sink;
+            while node != source {
+                let edge = parent_edge[node];
+                path.push(edge);
+                node = self.from(edge);
+            }
+
+            let amount = path.iter().map(|e| self.residual(*e)).min().unwrap();
+            for edge in path {
+                self.push(edge, amount);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn augments_along_the_paths_it_is_given() {
+        // source 0 -> 1 -> sink 2, with room for 3 on the first edge and 5 on the second
+        let mut network = FlowNetwork::new(3);
+        let first = network.add_edge(0, 1, 3);
+        let second = network.add_edge_with_cost(1, 2, 5, 7);
+        let mut paths = vec![vec![usize::MAX, first, second]];
+
+        network.augment(0, 2, |_| paths.pop());
+        assert_eq!((network.flow(first), network.flow(second)), (3, 3));
+        assert_eq!((network.residual(second), network.residual(second ^ 1)), (2, 3));
+        assert_eq!((network.cost(second ^ 1), network.from(second), network.to(second)), (-7, 1, 2));
+        assert_eq!(network.outflow(0), 3);
+        assert!(network.has_incoming(2) && !network.has_incoming(0));
+    }
+}
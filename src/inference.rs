@@ -0,0 +1,427 @@
+//! Works out what the opponent did last turn
+//!
+//! The board only shows the result of a turn, so the opponent's actions are reconstructed from
+//! the board before and after it, together with our own actions:
+//! new recyclers are builds, the change in matter gives what was spent,
+//! and the robots are matched up with a flow from the fields they started on (or a spawn)
+//! to the fields they ended on. Fights are undone with our own robots, which we know.
+//!
+//! Not everything can be told apart, e.g. two robots that swapped places,
+//! so whatever can't be is reported as an `Ambiguity`.
+
+use std::collections::VecDeque;
+use super::action::Action;
+use super::board::simulation::{BASE_INCOME, RECYCLER_COST, ROBOT_COST};
+use super::board::{Board, Coord, Field, Owner};
+use super::flow::{FlowNetwork, INFINITE};
+
+/// The opponent's actions of one turn, as far as they can be seen on the board
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpponentTurn {
+    pub builds: Vec<Coord>,
+    /// Fields and the number of robots spawned on them
+    pub spawns: Vec<(Coord, u32)>,
+    /// Robots that left their field; robots that stayed are not listed
+    pub moves: Vec<Flow>,
+    pub matter_spent: u32,
+    pub ambiguities: Vec<Ambiguity>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Flow {
+    pub from: Coord,
+    pub to: Coord,
+    pub amount: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Ambiguity {
+    /// `fields` were harvested by a recycler that was built and mined out in the same turn,
+    /// on one of `candidates`
+    HiddenBuild { fields: Vec<Coord>, candidates: Vec<Coord> },
+    /// Robots that could have reached these fields were removed when the fields turned to grass
+    LostToGrass(Vec<Coord>),
+    /// The matter spent doesn't add up to whole builds and spawns
+    Matter { spent: i64 },
+    /// The robots on these fields could have come from more than one place
+    Flows(Vec<Coord>),
+    /// No combination of moves and spawns explains the robots on the board
+    Unexplained,
+}
+
+impl OpponentTurn {
+
+    /// `my_actions` are the actions we sent on the `previous` turn, `current` is the board that followed
+    pub fn infer(previous: &Board, my_actions: &[Action], current: &Board) -> Self {
+        let mut ambiguities = Vec::new();
+
+        // The board after both players' builds, which is what robots moved on
+        let mut built = previous.clone();
+        built.apply_builds(my_actions, Owner::Me);
+        let mut builds = Vec::new();
+        for (before, after) in previous.fields.iter().zip(current.fields.iter()) {
+            if !before.has_recycler && after.has_recycler && after.owner == Owner::Opponent {
+                builds.push(before.coord());
+                built.fields[before.coord().index(built.width)].has_recycler = true;
+            }
+        }
+
+        // A recycler built on a field with 1 scrap is gone by the end of the turn,
+        // but its harvest still shows on the fields around it
+        let harvested = loop {
+            let harvested = built.process_harvest_cycle();
+            let fields = zip_coords(&harvested, current)
+                .filter(|(_, expected, actual)| actual.scrap_amount < expected.scrap_amount)
+                .map(|(coord, _, _)| coord)
+                .collect::<Vec<_>>();
+            if fields.is_empty() {
+                break harvested;
+            }
+
+            let candidates_for = |field: Coord| {
+                field
+                    .in_range(built.width, built.height)
+                    .filter(|c| is_hidden_build_candidate(&built, previous, current, *c))
+                    .collect::<Vec<_>>()
+            };
+            match fields.iter().map(|x| candidates_for(*x)).find(|x| x.len() == 1) {
+                Some(candidate) => {
+                    builds.push(candidate[0]);
+                    built.fields[candidate[0].index(built.width)].has_recycler = true;
+                },
+                None => {
+                    let mut candidates = fields.iter().flat_map(|x| candidates_for(*x)).collect::<Vec<_>>();
+                    candidates.sort();
+                    candidates.dedup();
+                    ambiguities.push(if candidates.is_empty() {
+                        Ambiguity::Unexplained
+                    } else {
+                        Ambiguity::HiddenBuild { fields, candidates }
+                    });
+                    break harvested;
+                },
+            }
+        };
+
+        let income = harvested.opponent_matter - built.opponent_matter;
+        let spent = previous.opponent_matter as i64 + income as i64 + BASE_INCOME as i64 - current.opponent_matter as i64;
+        let spawn_matter = spent - (builds.len() as u32 * RECYCLER_COST) as i64;
+        if spawn_matter < 0 || spawn_matter % ROBOT_COST as i64 != 0 {
+            ambiguities.push(Ambiguity::Matter { spent });
+        }
+        let spawned = (spawn_matter.max(0) / ROBOT_COST as i64) as u32;
+
+        let my_units = built.clone().spawn_and_move(my_actions, Owner::Me);
+        let (moves, spawns, flow_ambiguities) = match_robots(&built, current, &my_units, spawned);
+        ambiguities.extend(flow_ambiguities);
+
+        Self {
+            builds,
+            spawns,
+            moves,
+            matter_spent: spent.max(0) as u32,
+            ambiguities,
+        }
+    }
+
+    /// The actions that reproduce this turn, in the coordinates of the board they were inferred on
+    pub fn to_actions(&self) -> Vec<Action> {
+        let builds = self.builds
+            .iter()
+            .map(|c| Action::Build(c.x, c.y));
+        let spawns = self.spawns
+            .iter()
+            .map(|(c, amount)| Action::Spawn(*amount, c.x, c.y));
+        let moves = self.moves
+            .iter()
+            .map(|x| Action::Move { amount: x.amount, from: x.from.into(), to: x.to.into() });
+
+        builds.chain(spawns).chain(moves).collect()
+    }
+
+    pub fn is_ambiguous(&self) -> bool {
+        !self.ambiguities.is_empty()
+    }
+}
+
+fn zip_coords<'a>(a: &'a Board, b: &'a Board) -> impl Iterator<Item = (Coord, &'a Field, &'a Field)> {
+    a.fields
+        .iter()
+        .zip(b.fields.iter())
+        .map(|(x, y)| (x.coord(), x, y))
+}
+
+/// Whether the opponent could have built a recycler here that was mined out within the turn
+fn is_hidden_build_candidate(built: &Board, previous: &Board, current: &Board, coord: Coord) -> bool {
+    let before = &previous.fields[coord.index(previous.width)];
+    before.owner == Owner::Opponent
+        && before.scrap_amount == 1
+        && before.num_units == 0
+        && !built.fields[coord.index(built.width)].has_recycler
+        && current.fields[coord.index(current.width)].is_grass()
+}
+
+/// Matches the opponent's robots at the start of the turn, plus `spawned` new ones,
+/// with where they must have been before fights.
+///
+/// Returns the moves, the spawns and what couldn't be told apart.
+fn match_robots(built: &Board, current: &Board, my_units: &[u32], spawned: u32) -> (Vec<Flow>, Vec<(Coord, u32)>, Vec<Ambiguity>) {
+    let size = built.fields.len();
+    let mut ambiguities = Vec::new();
+
+    // Opponent robots on every field before fights, `None` for fields that turned to grass
+    let mut unexplained = false;
+    let arrived = (0..size)
+        .map(|i| {
+            let (before, after) = (&built.fields[i], &current.fields[i]);
+            if after.is_grass() && !before.is_grass() {
+                None
+            } else if after.owner == Owner::Opponent && after.num_units > 0 {
+                Some(after.num_units + my_units[i])
+            } else if after.owner == Owner::Me && after.num_units > 0 {
+                unexplained |= after.num_units > my_units[i];
+                Some(my_units[i].saturating_sub(after.num_units))
+            } else {
+                Some(my_units[i])
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let (source, sink, spawn, lost) = (0, 1, 2 + 2 * size, 3 + 2 * size);
+    let supply = |i: usize| 2 + i;
+    let demand = |i: usize| 2 + size + i;
+    let mut network = FlowNetwork::new(4 + 2 * size);
+
+    let mut total = spawned as i64;
+    network.add_edge(source, spawn, spawned as i64);
+    let mut stays = Vec::new();
+    let mut stay_edges = Vec::new();
+    let mut move_edges = Vec::new();
+    let mut spawn_edges = Vec::new();
+    let mut lost_fields = Vec::new();
+
+    for (i, field) in built.fields.iter().enumerate() {
+        let coord = field.coord();
+        let units = if field.owner == Owner::Opponent { field.num_units } else { 0 };
+        if units > 0 {
+            total += units as i64;
+            let from_source = network.add_edge(source, supply(i), units as i64);
+            let stay = network.add_edge(supply(i), demand(i), INFINITE);
+            stays.push((i, from_source, stay));
+            stay_edges.push((i, stay));
+
+            for neighbour in coord.neighbours(built.width, built.height) {
+                let j = neighbour.index(built.width);
+                if built.fields[j].is_traversible() {
+                    move_edges.push((coord, neighbour, network.add_edge(supply(i), demand(j), INFINITE)));
+                }
+            }
+        }
+
+        if field.owner == Owner::Opponent && field.is_traversible() {
+            spawn_edges.push((coord, network.add_edge(spawn, demand(i), INFINITE)));
+        }
+    }
+
+    let mut known = 0;
+    let mut to_sink = vec![None; size];
+    for (i, arrived) in arrived.iter().enumerate() {
+        match arrived {
+            Some(0) => (),
+            Some(amount) => {
+                known += *amount as i64;
+                to_sink[i] = Some(network.add_edge(demand(i), sink, *amount as i64));
+            },
+            None => {
+                network.add_edge(demand(i), lost, INFINITE);
+                lost_fields.push(i);
+            },
+        }
+    }
+    if known > total || (lost_fields.is_empty() && known != total) {
+        unexplained = true;
+    }
+    network.add_edge(lost, sink, (total - known).max(0));
+
+    // Robots most likely stayed where they could have, so start from that
+    for (i, from_source, stay) in stays {
+        if let (Some(to_sink), Some(arrived)) = (to_sink[i], arrived[i]) {
+            let amount = network.residual(from_source).min(arrived as i64);
+            for edge in [from_source, stay, to_sink] {
+                network.push(edge, amount);
+            }
+        }
+    }
+    max_flow(&mut network, source, sink);
+
+    if network.outflow(source) < total {
+        unexplained = true;
+    }
+    if unexplained {
+        ambiguities.push(Ambiguity::Unexplained);
+    }
+
+    // Robots might have walked onto a field that turned to grass
+    let lost_fields = lost_fields
+        .into_iter()
+        .filter(|i| network.has_incoming(demand(*i)))
+        .map(|i| Coord::from_index(i, built.width))
+        .collect::<Vec<_>>();
+    if !lost_fields.is_empty() {
+        ambiguities.push(Ambiguity::LostToGrass(lost_fields));
+    }
+
+    // Another explanation exists when robots on a field could have come from somewhere else instead
+    let arrivals = stay_edges
+        .iter()
+        .map(|(i, edge)| (*i, *edge))
+        .chain(move_edges.iter().map(|(_, to, edge)| (to.index(built.width), *edge)))
+        .chain(spawn_edges.iter().map(|(coord, edge)| (coord.index(built.width), *edge)));
+    let mut ambiguous = arrivals
+        .filter(|(_, edge)| network.flow(*edge) > 0 && can_reroute(&network, *edge))
+        .map(|(i, _)| Coord::from_index(i, built.width))
+        .collect::<Vec<_>>();
+    ambiguous.sort_by_key(|x| x.index(built.width));
+    ambiguous.dedup();
+    if !ambiguous.is_empty() {
+        ambiguities.push(Ambiguity::Flows(ambiguous));
+    }
+
+    let moves = move_edges
+        .into_iter()
+        .filter(|(_, _, edge)| network.flow(*edge) > 0)
+        .map(|(from, to, edge)| Flow { from, to, amount: network.flow(edge) as u32 })
+        .collect();
+    let spawns = spawn_edges
+        .into_iter()
+        .filter(|(_, edge)| network.flow(*edge) > 0)
+        .map(|(coord, edge)| (coord, network.flow(edge) as u32))
+        .collect();
+
+    (moves, spawns, ambiguities)
+}
+
+/// Edmonds-Karp: augments along shortest paths until the sink can't be reached
+fn max_flow(network: &mut FlowNetwork, source: usize, sink: usize) {
+    network.augment(source, sink, |network| {
+        let mut parent_edge = vec![usize::MAX; network.node_count()];
+        let mut frontier = VecDeque::from([source]);
+        while let Some(node) = frontier.pop_front() {
+            for &edge in network.edges_from(node) {
+                let to = network.to(edge);
+                if to != source && parent_edge[to] == usize::MAX && network.residual(edge) > 0 {
+                    parent_edge[to] = edge;
+                    frontier.push_back(to);
+                }
+            }
+        }
+        Some(parent_edge)
+    });
+}
+
+/// Whether the flow over `edge` could take another route, so the flow isn't the only one possible
+fn can_reroute(network: &FlowNetwork, edge: usize) -> bool {
+    let (from, to) = (network.from(edge), network.to(edge));
+
+    // Look for another path with residual capacity between the ends of the edge;
+    // sending flow back over the edge and along that path gives another valid flow
+    let mut visited = vec![false; network.node_count()];
+    let mut frontier = VecDeque::from([from]);
+    visited[from] = true;
+    while let Some(node) = frontier.pop_front() {
+        for &next_edge in network.edges_from(node) {
+            let next = network.to(next_edge);
+            if next_edge == edge || visited[next] || network.residual(next_edge) <= 0 {
+                continue;
+            }
+            if next == to {
+                return true;
+            }
+            visited[next] = true;
+            frontier.push_back(next);
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(previous: &Board, mine: &[Action], theirs: &[Action]) -> (Board, OpponentTurn) {
+        let current = previous.apply_turn(mine, theirs);
+        let inferred = OpponentTurn::infer(previous, mine, &current);
+        (current, inferred)
+    }
+
+    #[test]
+    fn finds_builds_spawns_and_moves() {
+        let previous = Board::from_ascii("
+            my_matter 20
+            opponent_matter 30
+            5m2 5  5  5o 5o 5o
+            5   5  5  5  5  5o3
+        ").unwrap();
+        let theirs = [
+            Action::Build(3, 0),
+            Action::Spawn(2, 4, 0),
+            Action::Move { amount: 2, from: (5, 1), to: (4, 1) },
+        ];
+        let mine = [Action::Move { amount: 2, from: (0, 0), to: (1, 0) }];
+
+        let (current, inferred) = play(&previous, &mine, &theirs);
+
+        assert_eq!(inferred.builds, vec![Coord::new(3, 0)]);
+        assert_eq!(inferred.spawns, vec![(Coord::new(4, 0), 2)]);
+        assert_eq!(inferred.moves, vec![Flow { from: Coord::new(5, 1), to: Coord::new(4, 1), amount: 2 }]);
+        assert_eq!(inferred.matter_spent, 30);
+        assert_eq!(inferred.ambiguities, vec![]);
+        assert_eq!(previous.apply_turn(&mine, &inferred.to_actions()).to_input(), current.to_input());
+    }
+
+    #[test]
+    fn undoes_fights_with_our_robots() {
+        let previous = Board::from_ascii("5m3 5 5o4").unwrap();
+        let mine = [Action::Move { amount: 3, from: (0, 0), to: (1, 0) }];
+        let theirs = [Action::Move { amount: 4, from: (2, 0), to: (1, 0) }];
+
+        let (current, inferred) = play(&previous, &mine, &theirs);
+
+        assert_eq!(current.get_field(1, 0).unwrap().num_units, 1);
+        assert_eq!(inferred.moves, vec![Flow { from: Coord::new(2, 0), to: Coord::new(1, 0), amount: 4 }]);
+        assert_eq!(inferred.ambiguities, vec![]);
+    }
+
+    #[test]
+    fn swapped_robots_are_ambiguous() {
+        let previous = Board::from_ascii("
+            5o1 5o
+            5o  5o1
+        ").unwrap();
+        let theirs = [
+            Action::Move { amount: 1, from: (0, 0), to: (1, 0) },
+            Action::Move { amount: 1, from: (1, 1), to: (0, 1) },
+        ];
+
+        let (_, inferred) = play(&previous, &[], &theirs);
+
+        assert_eq!(inferred.ambiguities, vec![Ambiguity::Flows(vec![Coord::new(1, 0), Coord::new(0, 1)])]);
+        assert_eq!(inferred.moves.iter().map(|x| x.amount).sum::<u32>(), 2);
+    }
+
+    #[test]
+    fn finds_recyclers_mined_out_within_the_turn() {
+        let previous = Board::from_ascii("
+            opponent_matter 10
+            5 5 1o 5o1
+        ").unwrap();
+
+        let (current, inferred) = play(&previous, &[], &[Action::Build(2, 0)]);
+
+        assert!(current.get_field(2, 0).unwrap().is_grass());
+        assert_eq!(inferred.builds, vec![Coord::new(2, 0)]);
+        assert_eq!(inferred.matter_spent, 10);
+        assert_eq!(inferred.ambiguities, vec![]);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod board;
+pub mod agent;
+pub mod action;
+pub mod rng;
+pub mod recorder;
+pub mod flow;
+pub mod inference;
+pub mod assignment;
+pub mod evaluation;
@@ -1,57 +1,87 @@
 use std::io;
 use std::time::Instant;
 
-macro_rules! parse_input {
-    ($x:expr, $t:ident) => ($x.trim().parse::<$t>().unwrap())
-}
+use codingame_keep_off_the_grass::board::boardbuilder::{BoardBuilder, InputError};
+use codingame_keep_off_the_grass::board::render::RenderMode;
+use codingame_keep_off_the_grass::agent::{Agent, TurnHistory};
+use codingame_keep_off_the_grass::agent::simple_economy::SimpleEconomyAgent;
+use codingame_keep_off_the_grass::action::Action;
+use codingame_keep_off_the_grass::action::validator::TurnValidator;
+use codingame_keep_off_the_grass::recorder::Recorder;
 
-mod board;
-mod agent;
-mod action;
+/// Writes the input of every turn to stderr, so the game can be replayed with the `replay` binary.
+/// Switch it on when a live game needs debugging.
+const RECORD_INPUT: bool = false;
 
-use board::Field;
-use board::boardbuilder::BoardBuilder;
-use board::boardbuilder::SizeKnown;
-use board::distance_board::DistanceBoard;
-use board::Owner;
-use agent::Agent;
-use agent::simple_economy::SimpleEconomyAgent;
-use action::Action;
+/// Draws the board and the agent's overlays to stderr, `Plain` fits the CodinGame panel
+const DEBUG_RENDER: Option<RenderMode> = None;
 
 /**
  * Auto-generated code below aims at helping you parse
  * the standard input according to the problem statement.
  **/
 fn main() {
-    let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
-    let inputs = input_line.split(" ").collect::<Vec<_>>();
-    let width = parse_input!(inputs[0], i32) as u32;
-    let height = parse_input!(inputs[1], i32) as u32;
+    let stdin = io::stdin();
+    let mut reader = stdin.lock();
+    let (width, height) = match BoardBuilder::size_from_reader(&mut reader) {
+        Ok(builder) => (builder.width(), builder.height()),
+        Err(e) => {
+            eprintln!("Could not read board size: {}", e);
+            return;
+        },
+    };
 
     let mut agent = SimpleEconomyAgent {
-        min_scrap_lead: 5,
-        recycler_min_score: 30,
-        expected_mining_discount: 0.8,
-        distance_move_weighting: 3,
-        distance_mine_weighting: 2,
-        recycler_robot_adjacency_weight: 8,
-        movement_own_score: 5,
-        movement_neutral_score: 0,
-        movement_opponent_score: 3,
+        debug_render: DEBUG_RENDER,
+        ..Default::default()
     };
 
+    let mut recorder = RECORD_INPUT.then(Recorder::default);
+    if let Some(recorder) = &recorder {
+        recorder.record_size(width, height);
+    }
+
+    let mut history = TurnHistory::default();
+
     // game loop
     loop {
+        let builder = match BoardBuilder::new(width, height).fields_from_reader(&mut reader) {
+            Ok(builder) => builder,
+            Err(InputError::Eof) => break, // the referee closed the pipe, game over
+            Err(e) => {
+                eprintln!("Could not read turn input: {}", e);
+                break;
+            },
+        };
+        // the clock starts once the input is there
         let start = Instant::now();
-        let builder = BoardBuilder::new(width, height).fields_from_stdin();
         let board = builder.build();
+        if let Some(recorder) = &mut recorder {
+            recorder.record_turn(&board);
+        }
+        let actions = history.play(&mut agent, &board, start);
+        let results = TurnValidator::new(&board).validate_turn(&actions);
 
-        let actions = agent.generate_actions(board);
-        Action::log_turn(actions);
+        let actions = actions
+            .into_iter()
+            .zip(results)
+            .filter_map(|(action, result)| match result {
+                Ok(()) => Some(action),
+                Err(e) => {
+                    eprintln!("Dropped invalid action {}: {}", action, e);
+                    None
+                },
+            })
+            .collect::<Vec<_>>();
+        Action::log_turn(actions.iter().cloned());
+        history.push(board, actions);
 
         let duration = start.elapsed();
         eprintln!("Time elapsed in micros: {}", duration.as_micros());
     }
+
+    if let Some(board) = history.boards.last() {
+        agent.on_game_end(board);
+    }
 }
 
@@ -0,0 +1,219 @@
+//! Records the turn input on stderr in a compact form, so games played on CodinGame can be replayed
+//!
+//! Every recorded line starts with `#REC`, so the lines can be picked out of any stderr dump:
+//! `#REC size <width> <height>` once, then `#REC turn <turn> <my matter> <opponent matter> <fields>`.
+//! Each field is written as three values: scrap amount, flags and number of units.
+//! A value below 62 is a single character of `0-9a-zA-Z`, larger values are written as `(decimal)`.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use super::board::{Board, Field, Owner};
+
+pub const RECORD_PREFIX: &str = "#REC";
+
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+#[derive(Default)]
+pub struct Recorder {
+    turn: u32,
+}
+
+impl Recorder {
+
+    pub fn record_size(&self, width: u32, height: u32) {
+        eprintln!("{}", encode_size(width, height));
+    }
+
+    pub fn record_turn(&mut self, board: &Board) {
+        self.turn += 1;
+        eprintln!("{}", encode_turn(self.turn, board));
+    }
+}
+
+/// All turns found in a log
+#[derive(Clone, Debug)]
+pub struct Recording {
+    pub width: u32,
+    pub height: u32,
+    /// `(turn, board)`, in the order they were recorded
+    pub turns: Vec<(u32, Board)>,
+}
+
+impl Recording {
+
+    /// Decodes all recorded lines in `log`, other lines are ignored
+    pub fn decode(log: &str) -> Result<Self, RecordError> {
+        let mut size = None;
+        let mut turns = Vec::new();
+
+        for (i, line) in log.lines().enumerate() {
+            let line_number = i + 1;
+            let tokens = match line.trim().strip_prefix(RECORD_PREFIX) {
+                Some(rest) => rest.split_whitespace().collect::<Vec<_>>(),
+                None => continue,
+            };
+            let number = |index: usize| -> Result<u32, RecordError> {
+                tokens.get(index).and_then(|x| x.parse().ok()).ok_or(RecordError::Invalid(line_number))
+            };
+
+            match tokens.first() {
+                Some(&"size") => size = Some((number(1)?, number(2)?)),
+                Some(&"turn") => {
+                    let (width, height) = size.ok_or(RecordError::MissingSize)?;
+                    let fields = tokens.get(4).ok_or(RecordError::Invalid(line_number))?;
+                    let board = Board {
+                        width,
+                        height,
+                        my_matter: number(2)?,
+                        opponent_matter: number(3)?,
+                        fields: decode_fields(fields, width, height).ok_or(RecordError::Invalid(line_number))?,
+                    };
+                    turns.push((number(1)?, board));
+                },
+                _ => return Err(RecordError::Invalid(line_number)),
+            }
+        }
+
+        let (width, height) = size.ok_or(RecordError::MissingSize)?;
+        Ok(Self {
+            width,
+            height,
+            turns,
+        })
+    }
+
+    pub fn get_turn(&self, turn: u32) -> Option<&Board> {
+        self.turns
+            .iter()
+            .find(|(x, _)| *x == turn)
+            .map(|(_, board)| board)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RecordError {
+    MissingSize,
+    /// The line number of the invalid record
+    Invalid(usize),
+}
+
+impl Display for RecordError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordError::MissingSize => write!(f, "no board size recorded before the turns"),
+            RecordError::Invalid(line) => write!(f, "invalid record on line {}", line),
+        }
+    }
+}
+
+impl Error for RecordError {}
+
+pub fn encode_size(width: u32, height: u32) -> String {
+    format!("{} size {} {}", RECORD_PREFIX, width, height)
+}
+
+pub fn encode_turn(turn: u32, board: &Board) -> String {
+    let mut fields = String::with_capacity(3 * board.fields.len());
+    for field in board.fields.iter() {
+        let flags = (field.owner.to_num() + 1) as u32
+            + 3 * (field.has_recycler as u32
+                | (field.can_build as u32) << 1
+                | (field.can_spawn as u32) << 2
+                | (field.in_recycler_range as u32) << 3);
+
+        for value in [field.scrap_amount, flags, field.num_units] {
+            encode_value(value, &mut fields);
+        }
+    }
+
+    format!("{} turn {} {} {} {}", RECORD_PREFIX, turn, board.my_matter, board.opponent_matter, fields)
+}
+
+fn encode_value(value: u32, result: &mut String) {
+    match DIGITS.get(value as usize) {
+        Some(digit) => result.push(*digit as char),
+        None => result.push_str(&format!("({})", value)),
+    }
+}
+
+fn decode_fields(encoded: &str, width: u32, height: u32) -> Option<Vec<Field>> {
+    let mut values = Vec::new();
+    let mut chars = encoded.chars();
+    while let Some(c) = chars.next() {
+        let value = if c == '(' {
+            chars.by_ref().take_while(|x| *x != ')').collect::<String>().parse().ok()?
+        } else {
+            DIGITS.iter().position(|x| *x as char == c)? as u32
+        };
+        values.push(value);
+    }
+
+    if values.len() != 3 * (width * height) as usize {
+        return None;
+    }
+
+    values
+        .chunks(3)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let (scrap_amount, flags, num_units) = (chunk[0], chunk[1], chunk[2]);
+            let bits = flags / 3;
+            Some(Field {
+                x: i as u32 % width,
+                y: i as u32 / width,
+                scrap_amount,
+                owner: Owner::from_num((flags % 3) as i32 - 1)?,
+                num_units,
+                has_recycler: bits & 1 != 0,
+                can_build: bits & 2 != 0,
+                can_spawn: bits & 4 != 0,
+                in_recycler_range: bits & 8 != 0,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_lines(board: &Board) -> Vec<String> {
+        board.fields.iter().map(|x| x.to_input_line()).collect()
+    }
+
+    #[test]
+    fn recorded_turns_decode_to_the_same_board() {
+        let mut board = Board::from_ascii("
+            my_matter 123
+            opponent_matter 7
+            5m2 9mR 4   .
+            3   1o  8oR 6o70
+        ").unwrap();
+        // too large for a single character
+        board.fields[0].scrap_amount = 62;
+        let log = [encode_size(4, 2), encode_turn(3, &board)].join("\n");
+
+        let recording = Recording::decode(&log).unwrap();
+        assert_eq!((recording.width, recording.height), (4, 2));
+        let decoded = recording.get_turn(3).unwrap();
+        assert_eq!((decoded.my_matter, decoded.opponent_matter), (123, 7));
+        assert_eq!(input_lines(decoded), input_lines(&board));
+        assert_eq!(decoded.fields[5].coord(), board.fields[5].coord());
+    }
+
+    #[test]
+    fn other_lines_are_skipped() {
+        let board = Board::from_ascii("5m1 5o1").unwrap();
+        let log = format!(
+            "Time elapsed in micros: 120\n{}\nDropped invalid action WAIT: nothing\n  {}\n",
+            encode_size(2, 1),
+            encode_turn(1, &board),
+        );
+
+        let recording = Recording::decode(&log).unwrap();
+        assert_eq!(recording.turns.len(), 1);
+        assert_eq!(Recording::decode(&encode_turn(1, &board)).unwrap_err(), RecordError::MissingSize);
+        assert_eq!(Recording::decode("x\n#REC turn 1 2").unwrap_err(), RecordError::MissingSize);
+        assert_eq!(Recording::decode("#REC size 2 1\n#REC turn 1 2 3 abc").unwrap_err(), RecordError::Invalid(2));
+    }
+}